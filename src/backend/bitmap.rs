@@ -0,0 +1,353 @@
+use bytes::Bytes;
+use crate::backend::{Backend, MAX_STRING_LEN};
+use crate::cmd::CommandError;
+
+/// BITCOUNT / BITPOS 中区间的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOpKind {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+/// BITFIELD 的类型，如 i8、u16
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFieldType {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitFieldOverflow {
+    #[default]
+    Wrap,
+    Sat,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldOp {
+    Get(BitFieldType, u64),
+    Set(BitFieldType, u64, i64),
+    IncrBy(BitFieldType, u64, i64),
+    Overflow(BitFieldOverflow),
+}
+
+// 最大的 bit 偏移，字符串不能超过 512MB
+pub const MAX_BIT_OFFSET: u64 = MAX_STRING_LEN as u64 * 8 - 1;
+
+impl BitFieldType {
+    /// 解析 i1..i64、u1..u63
+    pub fn parse(s: &str) -> Option<Self> {
+        let (signed, bits) = match s.as_bytes().first()? {
+            b'i' | b'I' => (true, s[1..].parse().ok()?),
+            b'u' | b'U' => (false, s[1..].parse().ok()?),
+            _ => return None,
+        };
+        let max = if signed { 64 } else { 63 };
+        (1..=max).contains(&bits).then_some(BitFieldType { signed, bits })
+    }
+
+    fn min(&self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max(&self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+
+    // 把原始位解释为该类型的值
+    fn decode(&self, raw: u64) -> i64 {
+        if self.signed && self.bits < 64 && raw & (1 << (self.bits - 1)) != 0 {
+            (raw | (!0u64 << self.bits)) as i64
+        } else {
+            raw as i64
+        }
+    }
+
+    // 按溢出策略把结果收敛到类型范围内，FAIL 时返回 None
+    fn fit(&self, n: i128, overflow: BitFieldOverflow) -> Option<i64> {
+        if (self.min()..=self.max()).contains(&n) {
+            return Some(n as i64);
+        }
+        match overflow {
+            BitFieldOverflow::Fail => None,
+            BitFieldOverflow::Sat if n > self.max() => Some(self.max() as i64),
+            BitFieldOverflow::Sat => Some(self.min() as i64),
+            BitFieldOverflow::Wrap => {
+                let mask = if self.bits == 64 { u64::MAX } else { (1u64 << self.bits) - 1 };
+                Some(self.decode(n as u64 & mask))
+            }
+        }
+    }
+}
+
+pub fn get_bit(bytes: &[u8], offset: u64) -> u8 {
+    let byte = (offset / 8) as usize;
+    match bytes.get(byte) {
+        Some(b) => (b >> (7 - offset % 8)) & 1,
+        None => 0,
+    }
+}
+
+// 写入一个 bit，字符串不够长时以 0 补齐，返回旧值
+pub fn set_bit(bytes: &mut Vec<u8>, offset: u64, bit: u8) -> u8 {
+    let byte = (offset / 8) as usize;
+    if bytes.len() <= byte {
+        bytes.resize(byte + 1, 0);
+    }
+    let mask = 1 << (7 - offset % 8);
+    let old = (bytes[byte] & mask != 0) as u8;
+    if bit == 1 {
+        bytes[byte] |= mask;
+    } else {
+        bytes[byte] &= !mask;
+    }
+    old
+}
+
+/// 把 start / end（可为负数）换算为 [0, total) 内的闭区间
+pub fn resolve_range(start: i64, end: i64, total: i64) -> Option<(u64, u64)> {
+    let start = if start < 0 { (total + start).max(0) } else { start };
+    let end = if end < 0 { (total + end).max(0) } else { end.min(total - 1) };
+    (total > 0 && start <= end).then_some((start as u64, end as u64))
+}
+
+// 把区间转换为 bit 下标
+fn bit_range(len: usize, start: i64, end: i64, unit: BitUnit) -> Option<(u64, u64)> {
+    match unit {
+        BitUnit::Byte => resolve_range(start, end, len as i64).map(|(s, e)| (s * 8, e * 8 + 7)),
+        BitUnit::Bit => resolve_range(start, end, len as i64 * 8),
+    }
+}
+
+pub fn bitcount(bytes: &[u8], range: Option<(i64, i64, BitUnit)>) -> u64 {
+    let (start, end, unit) = range.unwrap_or((0, -1, BitUnit::Byte));
+    let Some((first, last)) = bit_range(bytes.len(), start, end, unit) else {
+        return 0;
+    };
+    let mut count = 0;
+    let mut bit = first;
+    while bit <= last {
+        // 整字节直接 popcount
+        if bit % 8 == 0 && bit + 7 <= last {
+            count += bytes[(bit / 8) as usize].count_ones() as u64;
+            bit += 8;
+        } else {
+            count += get_bit(bytes, bit) as u64;
+            bit += 1;
+        }
+    }
+    count
+}
+
+/// BITPOS，end 为 None 表示没有指定结束位置
+pub fn bitpos(bytes: &[u8], bit: u8, start: i64, end: Option<i64>, unit: BitUnit) -> i64 {
+    if bytes.is_empty() {
+        return if bit == 1 { -1 } else { 0 };
+    }
+    let Some((first, last)) = bit_range(bytes.len(), start, end.unwrap_or(-1), unit) else {
+        return -1;
+    };
+    let skip = if bit == 1 { 0x00 } else { 0xff };
+    let mut pos = first;
+    while pos <= last {
+        if pos % 8 == 0 && pos + 7 <= last && bytes[(pos / 8) as usize] == skip {
+            pos += 8;
+            continue;
+        }
+        if get_bit(bytes, pos) == bit {
+            return pos as i64;
+        }
+        pos += 1;
+    }
+    // 找 0 且没有指定结束位置时，字符串右侧视为无限个 0
+    if bit == 0 && end.is_none() {
+        return last as i64 + 1;
+    }
+    -1
+}
+
+pub fn bitop<B: AsRef<[u8]>>(op: BitOpKind, sources: &[B]) -> Vec<u8> {
+    let len = sources.iter().map(|s| s.as_ref().len()).max().unwrap_or(0);
+    (0..len)
+        .map(|i| {
+            let mut bytes = sources.iter().map(|s| s.as_ref().get(i).copied().unwrap_or(0));
+            let first = bytes.next().unwrap_or(0);
+            match op {
+                BitOpKind::And => bytes.fold(first, |acc, b| acc & b),
+                BitOpKind::Or => bytes.fold(first, |acc, b| acc | b),
+                BitOpKind::Xor => bytes.fold(first, |acc, b| acc ^ b),
+                BitOpKind::Not => !first,
+            }
+        })
+        .collect()
+}
+
+fn read_bits(bytes: &[u8], offset: u64, bits: u32) -> u64 {
+    (0..bits as u64).fold(0, |acc, i| (acc << 1) | get_bit(bytes, offset + i) as u64)
+}
+
+fn write_bits(bytes: &mut Vec<u8>, offset: u64, bits: u32, value: u64) {
+    for i in 0..bits as u64 {
+        let bit = (value >> (bits as u64 - 1 - i)) & 1;
+        set_bit(bytes, offset + i, bit as u8);
+    }
+}
+
+/// 依次执行 BITFIELD 子命令，FAIL 溢出时对应结果为 None 且不写入
+pub fn bitfield(bytes: &mut Vec<u8>, ops: &[BitFieldOp]) -> Vec<Option<i64>> {
+    let mut overflow = BitFieldOverflow::default();
+    let mut results = Vec::with_capacity(ops.len());
+    for op in ops {
+        match *op {
+            BitFieldOp::Overflow(o) => overflow = o,
+            BitFieldOp::Get(ty, offset) => {
+                results.push(Some(ty.decode(read_bits(bytes, offset, ty.bits))));
+            }
+            BitFieldOp::Set(ty, offset, value) => {
+                let old = ty.decode(read_bits(bytes, offset, ty.bits));
+                match ty.fit(value as i128, overflow) {
+                    Some(new) => {
+                        write_bits(bytes, offset, ty.bits, new as u64);
+                        results.push(Some(old));
+                    }
+                    None => results.push(None),
+                }
+            }
+            BitFieldOp::IncrBy(ty, offset, incr) => {
+                let old = ty.decode(read_bits(bytes, offset, ty.bits));
+                match ty.fit(old as i128 + incr as i128, overflow) {
+                    Some(new) => {
+                        write_bits(bytes, offset, ty.bits, new as u64);
+                        results.push(Some(new));
+                    }
+                    None => results.push(None),
+                }
+            }
+        }
+    }
+    results
+}
+
+impl Backend {
+    pub fn setbit(&self, key: &[u8], offset: u64, bit: u8) -> Result<u8, CommandError> {
+        self.modify_string(key, |bytes| set_bit(bytes, offset, bit))
+    }
+
+    pub fn getbit(&self, key: &[u8], offset: u64) -> Result<u8, CommandError> {
+        Ok(get_bit(&self.get(key)?.unwrap_or_default(), offset))
+    }
+
+    /// BITOP，结果写入 dest 并返回长度；结果为空时删除 dest
+    pub fn bitop(&self, op: BitOpKind, dest: Bytes, keys: &[Bytes]) -> Result<usize, CommandError> {
+        // 所有源 key 在同一时刻读取，不会混入并发 MSET 的一半
+        let sources = self
+            .get_many(keys)
+            .into_iter()
+            .map(|v| v.map(Option::unwrap_or_default))
+            .collect::<Result<Vec<_>, _>>()?;
+        let result = bitop(op, &sources);
+        let len = result.len();
+        if len == 0 {
+            self.del(&dest);
+        } else {
+            self.set(dest, result.into());
+        }
+        Ok(len)
+    }
+
+    /// BITFIELD，只有读操作时不会创建 key
+    pub fn bitfield(&self, key: &[u8], ops: &[BitFieldOp]) -> Result<Vec<Option<i64>>, CommandError> {
+        let read_only = ops.iter().all(|op| matches!(op, BitFieldOp::Get(..) | BitFieldOp::Overflow(_)));
+        if read_only {
+            let mut bytes = Vec::from(self.get(key)?.unwrap_or_default());
+            return Ok(bitfield(&mut bytes, ops));
+        }
+        self.modify_string(key, |bytes| bitfield(bytes, ops))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setbit_getbit_bitcount() {
+        let mut bytes = vec![];
+        assert_eq!(set_bit(&mut bytes, 7, 1), 0);
+        assert_eq!(set_bit(&mut bytes, 7, 1), 1);
+        assert_eq!(bytes, vec![1]);
+        assert_eq!(get_bit(&bytes, 7), 1);
+        assert_eq!(get_bit(&bytes, 100), 0);
+
+        let bytes = b"foobar";
+        assert_eq!(bitcount(bytes, None), 26);
+        assert_eq!(bitcount(bytes, Some((0, 0, BitUnit::Byte))), 4);
+        assert_eq!(bitcount(bytes, Some((1, 1, BitUnit::Byte))), 6);
+        assert_eq!(bitcount(bytes, Some((5, 30, BitUnit::Bit))), 17);
+    }
+
+    #[test]
+    fn test_bitpos() {
+        assert_eq!(bitpos(&[0xff, 0xf0, 0x00], 0, 0, None, BitUnit::Byte), 12);
+        assert_eq!(bitpos(&[0x00, 0xff, 0xf0], 1, 2, None, BitUnit::Byte), 16);
+        assert_eq!(bitpos(&[0x00, 0xff, 0xf0], 1, 2, Some(-1), BitUnit::Byte), 16);
+        assert_eq!(bitpos(&[0x00, 0xff, 0xf0], 1, 7, Some(15), BitUnit::Bit), 8);
+        assert_eq!(bitpos(&[0xff, 0xff], 0, 0, None, BitUnit::Byte), 16);
+        assert_eq!(bitpos(&[0xff, 0xff], 0, 0, Some(-1), BitUnit::Byte), -1);
+        assert_eq!(bitpos(&[], 1, 0, None, BitUnit::Byte), -1);
+    }
+
+    #[test]
+    fn test_bitop() {
+        let sources = vec![b"foobar".to_vec(), b"abcdef".to_vec()];
+        assert_eq!(bitop(BitOpKind::And, &sources), b"`bc`ab".to_vec());
+        assert_eq!(bitop(BitOpKind::Not, &[vec![0x0f]]), vec![0xf0]);
+        assert_eq!(bitop(BitOpKind::Or, &[vec![0x01], vec![0x10, 0x01]]), vec![0x11, 0x01]);
+    }
+
+    #[test]
+    fn test_bitfield() {
+        let i8 = BitFieldType::parse("i8").unwrap();
+        let u4 = BitFieldType::parse("u4").unwrap();
+        assert!(BitFieldType::parse("u64").is_none());
+        assert!(BitFieldType::parse("i0").is_none());
+
+        let mut bytes = vec![];
+        let ret = bitfield(
+            &mut bytes,
+            &[
+                BitFieldOp::IncrBy(i8, 0, 100),
+                BitFieldOp::IncrBy(i8, 0, 100),
+                BitFieldOp::Get(u4, 0),
+                BitFieldOp::Overflow(BitFieldOverflow::Sat),
+                BitFieldOp::IncrBy(i8, 0, -1000),
+                BitFieldOp::Overflow(BitFieldOverflow::Fail),
+                BitFieldOp::Set(u4, 8, 16),
+                BitFieldOp::Set(u4, 8, 15),
+            ],
+        );
+        assert_eq!(
+            ret,
+            vec![Some(100), Some(-56), Some(12), Some(-128), None, Some(0)]
+        );
+        assert_eq!(bytes, vec![0x80, 0xf0]);
+    }
+}
@@ -0,0 +1,364 @@
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::backend::{Backend, RedisValue, ValueEntry};
+use bytes::Bytes;
+
+// 每次从字段过期索引中取出的到期字段数量
+const ACTIVE_EXPIRE_BATCH: usize = 64;
+// 与 Redis 的 ACTIVE_EXPIRE_CYCLE_KEYS_PER_LOOP 一致，每轮抽查的 key 数量
+const ACTIVE_EXPIRE_KEYS_PER_LOOP: usize = 20;
+// 每抽查一个 key 最多跳过的空槽位，避免在稀疏的分片上空转
+const ACTIVE_EXPIRE_EMPTY_BUCKETS_PER_KEY: usize = 20;
+// 单次主动过期最多占用的时间，避免长时间占用线程
+const ACTIVE_EXPIRE_TIME_LIMIT: Duration = Duration::from_millis(25);
+
+/// EXPIRE 系列命令的 NX / XX / GT / LT 选项，全部为 false 时总是设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExpireCondition {
+    // 仅当 key 没有过期时间时设置
+    pub nx: bool,
+    // 仅当 key 已有过期时间时设置
+    pub xx: bool,
+    // 仅当新的过期时间大于当前值时设置，没有过期时间视为无穷大
+    pub gt: bool,
+    // 仅当新的过期时间小于当前值时设置
+    pub lt: bool,
+}
+
+impl ExpireCondition {
+    pub(crate) fn allows(&self, current: Option<i64>, new: i64) -> bool {
+        match current {
+            None => !self.xx && !self.gt,
+            Some(current) => {
+                !self.nx && (!self.gt || new > current) && (!self.lt || new < current)
+            }
+        }
+    }
+}
+
+/// 写入值时如何处理过期时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpireUpdate {
+    // 清除过期时间（SET 的默认行为，GETEX 的 PERSIST）
+    #[default]
+    Clear,
+    // 保留原有的过期时间（SET 的 KEEPTTL，GETEX 不带选项）
+    Keep,
+    // 设置为指定的 unix 毫秒时间
+    At(i64),
+}
+
+/// 当前 unix 时间（毫秒）
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+impl Backend {
+    /// 设置过期时间（unix 毫秒）。条件不满足或 key 不存在时返回 false，
+    /// 时间已经过去则直接删除 key。
+    pub fn expire_at(&self, key: &[u8], at: i64, cond: ExpireCondition) -> bool {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return false;
+        };
+        if !cond.allows(entry.expire_at, at) {
+            return false;
+        }
+        if at <= now_ms() {
+            drop(entry);
+            self.del(key);
+            return true;
+        }
+        self.unindex_expire(key, entry.expire_at);
+        self.index_expire(key, Some(at));
+        entry.expire_at = Some(at);
+        true
+    }
+
+    /// 移除过期时间，key 原本有过期时间时返回 true
+    pub fn persist(&self, key: &[u8]) -> bool {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return false;
+        };
+        match entry.expire_at.take() {
+            Some(at) => {
+                self.unindex_expire(key, Some(at));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 返回过期时间（unix 毫秒），key 不存在为 -2，没有过期时间为 -1
+    pub fn pexpiretime(&self, key: &[u8]) -> i64 {
+        match self.get_entry(key) {
+            Some(entry) => entry.expire_at.unwrap_or(-1),
+            None => -2,
+        }
+    }
+
+    /// 主动过期：与 Redis 的 activeExpireCycle 一样，从上次的游标处继续扫描带过期时间的 key，
+    /// 每轮抽查 20 个并删除其中已过期的，过期比例不超过 25% 时认为剩下的不多，结束本次回收。
+    ///
+    /// 每次只锁住单个分片，并受时间上限约束，不会长时间阻塞 keyspace。返回删除的数量。
+    pub fn active_expire_cycle(&self) -> usize {
+        let start = Instant::now();
+        let mut reaped = 0;
+        let mut sampled = Vec::with_capacity(ACTIVE_EXPIRE_KEYS_PER_LOOP);
+        loop {
+            let cursor = self.expire_cursor.load(Ordering::Relaxed);
+            let next = self.scan_expires(cursor, ACTIVE_EXPIRE_KEYS_PER_LOOP, &mut sampled);
+            self.expire_cursor.store(next, Ordering::Relaxed);
+            let now = now_ms();
+            let total = sampled.len();
+            let mut expired = 0;
+            for (key, at) in sampled.drain(..) {
+                if at > now {
+                    continue;
+                }
+                expired += 1;
+                // 扫描后 key 可能已被改写，只删除过期时间仍然匹配的 key
+                if let Some((key, entry)) = self.keyspace.remove_if(&key, |_, e| e.expire_at == Some(at)) {
+                    self.unindex_entry(&key, &entry);
+                    reaped += 1;
+                }
+            }
+            // 扫完一整轮，或者过期的比例已经很低
+            if next == 0 || expired * 4 <= total || start.elapsed() > ACTIVE_EXPIRE_TIME_LIMIT {
+                break;
+            }
+        }
+        reaped + self.active_expire_fields(start)
+    }
+
+    // 从游标处依次扫描 expires 的分片和槽位，取出最多 n 个 (key, 过期时间)，返回下一次的游标。
+    // 游标高 32 位是分片下标，低 32 位是槽位下标；扫完所有分片时返回 0。
+    // 扫描期间分片扩容可能导致个别 key 被跳过或重复，下一轮扫描会补上，与 Redis 的 dictScan 一样。
+    fn scan_expires(&self, cursor: u64, n: usize, out: &mut Vec<(Bytes, i64)>) -> u64 {
+        let shards = self.expires.shards();
+        let (mut shard_index, mut bucket) = ((cursor >> 32) as usize, (cursor as u32) as usize);
+        let mut budget = n * ACTIVE_EXPIRE_EMPTY_BUCKETS_PER_KEY;
+        while shard_index < shards.len() {
+            let shard = shards[shard_index].read();
+            while bucket < shard.buckets() {
+                // SAFETY: 持有分片的读锁，bucket 小于 buckets()
+                let full = unsafe { shard.is_bucket_full(bucket) };
+                if full {
+                    // SAFETY: 槽位已占用且下标合法；引用只在读锁内使用，key 被克隆后才释放锁
+                    let (key, at) = unsafe { shard.bucket(bucket).as_ref() };
+                    out.push((key.clone(), *at.get()));
+                }
+                bucket += 1;
+                budget = budget.saturating_sub(1);
+                if out.len() >= n || budget == 0 {
+                    return ((shard_index as u64) << 32) | bucket as u64;
+                }
+            }
+            shard_index += 1;
+            bucket = 0;
+        }
+        0
+    }
+
+    // 回收已到期的 hash 字段，字段全部回收后删除 key；与 key 共用同一个时间上限
+    fn active_expire_fields(&self, start: Instant) -> usize {
+        let mut reaped = 0;
+        loop {
+            let now = now_ms();
+            let due = {
+                let mut expires = self.field_expires.lock().unwrap_or_else(|e| e.into_inner());
+                let mut due = Vec::with_capacity(ACTIVE_EXPIRE_BATCH);
+                while due.len() < ACTIVE_EXPIRE_BATCH {
+                    match expires.first() {
+                        Some((at, _, _)) if *at <= now => due.push(expires.pop_first().unwrap()),
+                        _ => break,
+                    }
+                }
+                due
+            };
+            let batch = due.len();
+            for (at, key, field) in due {
+                let removed = self.keyspace.remove_if_mut(&key, |_, entry| match &mut entry.value {
+                    RedisValue::Hash(hash) => {
+                        if hash.remove_expired(&field, at) {
+                            reaped += 1;
+                        }
+                        // 其余过期字段在各自的索引项到期时回收，这里只看是否已经删空
+                        hash.is_drained()
+                    }
+                    _ => false,
+                });
+                if let Some((key, entry)) = removed {
+                    self.unindex_entry(&key, &entry);
+                }
+            }
+            if batch < ACTIVE_EXPIRE_BATCH || start.elapsed() > ACTIVE_EXPIRE_TIME_LIMIT {
+                return reaped;
+            }
+        }
+    }
+
+    // 在持有 key 写锁时更新过期时间，同时维护过期索引
+    pub(crate) fn update_expire(&self, key: &[u8], entry: &mut ValueEntry, update: ExpireUpdate) {
+        let at = match update {
+            ExpireUpdate::Keep => return,
+            ExpireUpdate::Clear => None,
+            ExpireUpdate::At(at) => Some(at),
+        };
+        if entry.expire_at != at {
+            self.unindex_expire(key, entry.expire_at);
+            self.index_expire(key, at);
+            entry.expire_at = at;
+        }
+    }
+
+    // 调用方持有 key 在 keyspace 中的写锁，expires 的分片锁总是在它之后获取
+    pub(crate) fn index_expire(&self, key: &[u8], at: Option<i64>) {
+        if let Some(at) = at {
+            self.expires.insert(Bytes::copy_from_slice(key), at);
+        }
+    }
+
+    pub(crate) fn unindex_expire(&self, key: &[u8], at: Option<i64>) {
+        if let Some(at) = at {
+            self.expires.remove_if(key, |_, v| *v == at);
+        }
+    }
+
+    // key 连同它的值被删除或覆盖时，移除 key 以及 hash 字段的全部过期索引项
+    pub(crate) fn unindex_entry(&self, key: &[u8], entry: &ValueEntry) {
+        self.unindex_expire(key, entry.expire_at);
+        self.unindex_field_expires(key, &entry.value);
+    }
+
+    // 字段的过期时间被设置、清除或随字段删除时都要同步维护索引，
+    // 回收时仍会再次核对字段的过期时间
+    pub(crate) fn index_field_expire(&self, key: &[u8], field: &[u8], at: i64) {
+        let mut expires = self.field_expires.lock().unwrap_or_else(|e| e.into_inner());
+        expires.insert((at, Bytes::copy_from_slice(key), Bytes::copy_from_slice(field)));
+    }
+
+    pub(crate) fn unindex_field_expire(&self, key: &[u8], field: &[u8], at: Option<i64>) {
+        if let Some(at) = at {
+            let mut expires = self.field_expires.lock().unwrap_or_else(|e| e.into_inner());
+            expires.remove(&(at, Bytes::copy_from_slice(key), Bytes::copy_from_slice(field)));
+        }
+    }
+
+    // RENAME 时把 hash 字段的过期索引项从 key 移到 newkey；调用方持有 newkey 的写锁
+    pub(crate) fn move_field_expires(&self, key: &[u8], newkey: &[u8], value: &RedisValue) {
+        let RedisValue::Hash(hash) = value else { return };
+        let mut fields = hash.volatile_fields().peekable();
+        if fields.peek().is_none() {
+            return;
+        }
+        let (key, newkey) = (Bytes::copy_from_slice(key), Bytes::copy_from_slice(newkey));
+        let mut expires = self.field_expires.lock().unwrap_or_else(|e| e.into_inner());
+        for (field, at) in fields {
+            expires.remove(&(at, key.clone(), field.clone()));
+            expires.insert((at, newkey.clone(), field));
+        }
+    }
+
+    // 清除某个 hash 值所有字段的过期索引项
+    pub(crate) fn unindex_field_expires(&self, key: &[u8], value: &RedisValue) {
+        let RedisValue::Hash(hash) = value else { return };
+        let mut fields = hash.volatile_fields().peekable();
+        if fields.peek().is_none() {
+            return;
+        }
+        let key = Bytes::copy_from_slice(key);
+        let mut expires = self.field_expires.lock().unwrap_or_else(|e| e.into_inner());
+        for (field, at) in fields {
+            expires.remove(&(at, key.clone(), field));
+        }
+    }
+
+    // 惰性删除：只有在 key 仍然过期时才删除，避免误删被并发改写的值
+    pub(crate) fn remove_expired(&self, key: &[u8]) {
+        let now = now_ms();
+        if let Some((key, entry)) = self.keyspace.remove_if(key, |_, e| e.is_expired(now)) {
+            self.unindex_entry(&key, &entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expire_condition() {
+        let nx = ExpireCondition { nx: true, ..Default::default() };
+        assert!(nx.allows(None, 10));
+        assert!(!nx.allows(Some(5), 10));
+        let xx = ExpireCondition { xx: true, ..Default::default() };
+        assert!(!xx.allows(None, 10));
+        let gt = ExpireCondition { gt: true, ..Default::default() };
+        assert!(gt.allows(Some(5), 10));
+        assert!(!gt.allows(None, 10));
+        let lt = ExpireCondition { lt: true, ..Default::default() };
+        assert!(lt.allows(None, 10));
+        assert!(!lt.allows(Some(5), 10));
+        let xx_lt = ExpireCondition { xx: true, lt: true, ..Default::default() };
+        assert!(!xx_lt.allows(None, 10));
+        assert!(xx_lt.allows(Some(50), 10));
+    }
+
+    #[test]
+    fn test_lazy_and_active_expire() {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"a"), Bytes::from_static(b"1"));
+        backend.set(Bytes::from_static(b"b"), Bytes::from_static(b"2"));
+        backend.set(Bytes::from_static(b"c"), Bytes::from_static(b"3"));
+
+        let future = now_ms() + 100_000;
+        assert!(backend.expire_at(b"c", future, ExpireCondition::default()));
+        assert_eq!(backend.pexpiretime(b"c"), future);
+
+        // 直接写入一个已过期的时间，模拟时间流逝
+        for key in [b"a".as_slice(), b"b"] {
+            let mut entry = backend.keyspace.get_mut(key).unwrap();
+            entry.expire_at = Some(1);
+            backend.index_expire(key, Some(1));
+        }
+        assert_eq!(backend.get(b"a").unwrap(), None);
+        assert_eq!(backend.pexpiretime(b"a"), -2);
+        assert_eq!(backend.dbsize(), 2);
+
+        assert_eq!(backend.active_expire_cycle(), 1);
+        assert_eq!(backend.dbsize(), 1);
+        assert!(backend.persist(b"c"));
+        assert_eq!(backend.pexpiretime(b"c"), -1);
+    }
+
+    #[test]
+    fn test_active_expire_scans_all_volatile_keys() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            let key = Bytes::from(format!("key:{}", i));
+            backend.set(key.clone(), Bytes::from_static(b"v"));
+            if i % 2 == 0 {
+                let mut entry = backend.keyspace.get_mut(&key).unwrap();
+                entry.expire_at = Some(1);
+                backend.index_expire(&key, Some(1));
+            } else if i % 4 == 1 {
+                assert!(backend.expire_at(&key, now_ms() + 100_000, ExpireCondition::default()));
+            }
+        }
+        assert_eq!(backend.expires.len(), 750);
+
+        // 过期比例高时一次回收会连续扫描多轮，几次之后应当全部回收
+        let mut reaped = 0;
+        for _ in 0..100 {
+            reaped += backend.active_expire_cycle();
+            if reaped == 500 {
+                break;
+            }
+        }
+        assert_eq!(reaped, 500);
+        assert_eq!(backend.dbsize(), 500);
+        assert_eq!(backend.expires.len(), 250);
+    }
+}
@@ -0,0 +1,820 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+
+use bytes::Bytes;
+use dashmap::mapref::multiple::RefMulti;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+
+use crate::backend::{
+    glob_match, incr_f64, now_ms, parse_f64, parse_i64, random_u64, Backend, ExpireCondition,
+    RedisValue,
+};
+use crate::cmd::CommandError;
+
+/// hash 中的一个字段，可以带有单独的过期时间（unix 毫秒）
+#[derive(Debug)]
+pub struct HashField {
+    pub(crate) value: Bytes,
+    pub(crate) expire_at: Option<i64>,
+    // 字段在 `HashValue::slots` 中的下标
+    slot: usize,
+}
+
+impl HashField {
+    fn new(value: Bytes, slot: usize) -> Self {
+        Self { value, expire_at: None, slot }
+    }
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// hash 的字段表。已过期但尚未回收的字段对所有读操作不可见。
+///
+/// 修改字段需要 `&mut self`，调用方持有 key 的写锁，
+/// 因此 `volatile` 不需要额外同步。
+#[derive(Debug, Default)]
+pub struct HashValue {
+    fields: DashMap<Bytes, HashField>,
+    // 所有字段（包括尚未回收的过期字段）的紧凑列表，随机取样时按下标访问
+    slots: Vec<Bytes>,
+    // 按 (字段哈希, 字段) 排序的索引，HSCAN 从游标位置继续遍历
+    order: BTreeSet<(u64, Bytes)>,
+    // 带过期时间的字段数，为 0 时可以跳过过期检查
+    volatile: usize,
+    // 随覆盖或删除而失去过期时间的字段，由 Backend 取走后从字段过期索引中移除
+    dropped_expires: Vec<(Bytes, i64)>,
+}
+
+impl HashValue {
+    pub fn get(&self, field: &[u8]) -> Option<Ref<'_, Bytes, HashField>> {
+        let entry = self.fields.get(field)?;
+        if self.volatile > 0 && entry.is_expired(now_ms()) {
+            return None;
+        }
+        Some(entry)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RefMulti<'_, Bytes, HashField>> {
+        let now = now_ms();
+        self.fields.iter().filter(move |f| !f.is_expired(now))
+    }
+
+    pub fn len(&self) -> usize {
+        match self.volatile {
+            0 => self.fields.len(),
+            _ => self.iter().count(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.volatile {
+            0 => self.fields.is_empty(),
+            _ => self.iter().next().is_none(),
+        }
+    }
+
+    /// 写入字段并清除其过期时间，返回是否新增了字段
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        let old = self.fields.get_mut(&field).map(|mut old| {
+            old.value = value.clone();
+            (old.is_expired(now_ms()), old.expire_at.take())
+        });
+        if let Some((expired, at)) = old {
+            self.forget_expire(&field, at);
+            return expired;
+        }
+        self.slots.push(field.clone());
+        self.order.insert((field_hash(&field), field.clone()));
+        self.fields.insert(field, HashField::new(value, self.slots.len() - 1));
+        true
+    }
+
+    /// 删除字段，返回被删除的字段是否可见
+    pub fn remove(&mut self, field: &[u8]) -> bool {
+        match self.fields.remove(field) {
+            Some((field, old)) => {
+                self.order.remove(&(field_hash(&field), field.clone()));
+                // 最后一个字段移到被删除字段的位置上
+                self.slots.swap_remove(old.slot);
+                if let Some(mut moved) = self.slots.get(old.slot).and_then(|f| self.fields.get_mut(f)) {
+                    moved.slot = old.slot;
+                }
+                self.forget_expire(&field, old.expire_at);
+                !old.is_expired(now_ms())
+            }
+            None => false,
+        }
+    }
+
+    // 取出字段以便原地修改，不存在或已过期时用 `default` 创建，已有的过期时间保持不变
+    fn get_or_insert_mut(
+        &mut self,
+        field: Bytes,
+        default: impl FnOnce() -> Bytes,
+    ) -> RefMut<'_, Bytes, HashField> {
+        if self.fields.get(&field).is_some_and(|f| f.is_expired(now_ms())) {
+            self.remove(&field);
+        }
+        if !self.fields.contains_key(&field) {
+            self.insert(field.clone(), default());
+        }
+        self.fields.get_mut(&field).expect("field was just inserted")
+    }
+
+    // 修改字段的过期时间，返回原来的过期时间；调用方需确保字段存在
+    fn set_expire(&mut self, field: &[u8], at: Option<i64>) -> Option<i64> {
+        let old = self
+            .fields
+            .get_mut(field)
+            .and_then(|mut f| std::mem::replace(&mut f.expire_at, at));
+        match (old, at) {
+            (None, Some(_)) => self.volatile += 1,
+            (Some(_), None) => self.volatile -= 1,
+            _ => {}
+        }
+        old
+    }
+
+    /// 主动过期：只有过期时间仍为 `at` 时才删除字段
+    pub(crate) fn remove_expired(&mut self, field: &[u8], at: i64) -> bool {
+        if self.fields.get(field).is_some_and(|f| f.expire_at == Some(at)) {
+            self.remove(field);
+            return true;
+        }
+        false
+    }
+
+    // 所有带过期时间的字段（包括尚未回收的过期字段）及其过期时间
+    pub(crate) fn volatile_fields(&self) -> impl Iterator<Item = (Bytes, i64)> + '_ {
+        (self.volatile > 0)
+            .then(|| self.fields.iter().filter_map(|f| Some((f.key().clone(), f.expire_at?))))
+            .into_iter()
+            .flatten()
+    }
+
+    // 所有字段（包括尚未回收的过期字段）都已被删除
+    pub(crate) fn is_drained(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// 随机取样，count 为正数时返回不重复的字段，为负数时可以重复。
+    ///
+    /// 按 `slots` 的下标取样，只有 count 接近字段总数时才需要遍历整个 hash。
+    pub(crate) fn random_fields(&self, count: i64) -> FieldValues {
+        let now = now_ms();
+        if count < 0 {
+            let n = count.unsigned_abs() as usize;
+            let mut picked = Vec::with_capacity(n.min(self.slots.len()));
+            for _ in 0..n {
+                match self.random_slot(now) {
+                    Some(slot) => picked.push(self.field_at(slot)),
+                    // 过期字段太多，退回到遍历未过期的字段
+                    None => return sample_with_repeats(self.live_fields(now), n),
+                }
+            }
+            return picked;
+        }
+        let n = count as usize;
+        // count 接近字段总数时，遍历比反复取样更快
+        if n.saturating_mul(3) > self.slots.len() {
+            return sample_distinct(self.live_fields(now), n);
+        }
+        let mut seen = HashSet::with_capacity(n);
+        let mut picked = Vec::with_capacity(n);
+        while picked.len() < n {
+            let Some(slot) = self.random_slot(now) else {
+                return sample_distinct(self.live_fields(now), n);
+            };
+            if seen.insert(slot) {
+                picked.push(self.field_at(slot));
+            }
+        }
+        picked
+    }
+
+    // 从哈希值不小于 cursor 的字段开始取 count 个，多取一个用于确定下一个游标
+    fn scan(&self, cursor: u64, count: usize, pattern: Option<&[u8]>) -> (u64, FieldValues) {
+        let mut batch = self.order.range((cursor, Bytes::new())..);
+        let items = batch
+            .by_ref()
+            .take(count)
+            .filter(|(_, field)| pattern.is_none_or(|p| glob_match(p, field)))
+            .filter_map(|(_, field)| self.get(field).map(|v| (field.clone(), v.value.clone())))
+            .collect();
+        let next = batch.next().map_or(0, |(h, _)| *h);
+        (next, items)
+    }
+
+    // 随机选一个未过期字段的下标，连续多次选中过期字段时放弃
+    fn random_slot(&self, now: i64) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        (0..RANDOM_SLOT_TRIES)
+            .map(|_| (random_u64() % self.slots.len() as u64) as usize)
+            .find(|&slot| self.volatile == 0 || self.fields.get(&self.slots[slot]).is_some_and(|f| !f.is_expired(now)))
+    }
+
+    fn field_at(&self, slot: usize) -> (Bytes, Bytes) {
+        let field = &self.slots[slot];
+        let value = self.fields.get(field).map(|f| f.value.clone()).unwrap_or_default();
+        (field.clone(), value)
+    }
+
+    fn live_fields(&self, now: i64) -> FieldValues {
+        self.fields
+            .iter()
+            .filter(|f| !f.is_expired(now))
+            .map(|f| (f.key().clone(), f.value.clone()))
+            .collect()
+    }
+
+    fn forget_expire(&mut self, field: &Bytes, at: Option<i64>) {
+        if let Some(at) = at {
+            self.volatile -= 1;
+            self.dropped_expires.push((field.clone(), at));
+        }
+    }
+
+    // 取走自上次调用以来失去过期时间的字段
+    pub(crate) fn take_dropped_expires(&mut self) -> Vec<(Bytes, i64)> {
+        std::mem::take(&mut self.dropped_expires)
+    }
+}
+
+/// 字段与值的列表，HGETALL / HSCAN / HRANDFIELD 的返回结果
+pub type FieldValues = Vec<(Bytes, Bytes)>;
+
+// 随机取样时连续选中过期字段的次数上限，超过后改为遍历
+const RANDOM_SLOT_TRIES: usize = 64;
+
+impl Backend {
+    pub fn hget(&self, key: &[u8], field: &[u8]) -> Result<Option<Bytes>, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| hash.get(field).map(|v| v.value.clone()))?
+            .flatten())
+    }
+
+    /// HSET，返回新增的字段数，已存在的字段只更新值
+    pub fn hset(&self, key: Bytes, fields: FieldValues) -> Result<usize, CommandError> {
+        self.write_hash(key, |hash| {
+            let mut added = 0;
+            for (field, value) in fields {
+                if hash.insert(field, value) {
+                    added += 1;
+                }
+            }
+            added
+        })
+    }
+
+    /// HSETNX，字段已存在时不写入，返回是否写入
+    pub fn hsetnx(&self, key: Bytes, field: Bytes, value: Bytes) -> Result<bool, CommandError> {
+        self.write_hash(key, |hash| {
+            if hash.get(&field).is_some() {
+                return false;
+            }
+            hash.insert(field, value)
+        })
+    }
+
+    pub fn hgetall(&self, key: &[u8]) -> Result<FieldValues, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| {
+                hash.iter()
+                    .map(|v| (v.key().clone(), v.value.clone()))
+                    .collect()
+            })?
+            .unwrap_or_default())
+    }
+
+    /// HDEL，返回删除的字段数；最后一个字段被删除时 key 也一并删除
+    pub fn hdel(&self, key: &[u8], fields: &[Bytes]) -> Result<usize, CommandError> {
+        Ok(self
+            .modify_hash(key, |hash| fields.iter().filter(|f| hash.remove(f)).count())?
+            .unwrap_or(0))
+    }
+
+    pub fn hexists(&self, key: &[u8], field: &[u8]) -> Result<bool, CommandError> {
+        Ok(self.read_hash(key, |hash| hash.get(field).is_some())?.unwrap_or(false))
+    }
+
+    pub fn hlen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        Ok(self.read_hash(key, |hash| hash.len())?.unwrap_or(0))
+    }
+
+    pub fn hkeys(&self, key: &[u8]) -> Result<Vec<Bytes>, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| hash.iter().map(|v| v.key().clone()).collect())?
+            .unwrap_or_default())
+    }
+
+    pub fn hvals(&self, key: &[u8]) -> Result<Vec<Bytes>, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| hash.iter().map(|v| v.value.clone()).collect())?
+            .unwrap_or_default())
+    }
+
+    /// HMGET，不存在的字段返回 None
+    pub fn hmget(&self, key: &[u8], fields: &[Bytes]) -> Result<Vec<Option<Bytes>>, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| {
+                fields
+                    .iter()
+                    .map(|f| hash.get(f).map(|v| v.value.clone()))
+                    .collect()
+            })?
+            .unwrap_or_else(|| vec![None; fields.len()]))
+    }
+
+    pub fn hstrlen(&self, key: &[u8], field: &[u8]) -> Result<usize, CommandError> {
+        Ok(self
+            .read_hash(key, |hash| hash.get(field).map_or(0, |v| v.value.len()))?
+            .unwrap_or(0))
+    }
+
+    /// HINCRBY，字段不存在时视为 0；整个读改写在字段锁内完成
+    pub fn hincrby(&self, key: Bytes, field: Bytes, delta: i64) -> Result<i64, CommandError> {
+        self.write_hash(key, |hash| {
+            let mut entry = hash.get_or_insert_mut(field, || Bytes::from_static(b"0"));
+            let current = parse_i64(&entry.value).ok_or_else(|| {
+                CommandError::InvalidArgument("hash value is not an integer".to_string())
+            })?;
+            let n = current.checked_add(delta).ok_or_else(|| {
+                CommandError::InvalidArgument("increment or decrement would overflow".to_string())
+            })?;
+            entry.value = n.to_string().into();
+            Ok(n)
+        })?
+    }
+
+    /// HINCRBYFLOAT，返回写入后的字符串形式
+    pub fn hincrbyfloat(&self, key: Bytes, field: Bytes, delta: f64) -> Result<Bytes, CommandError> {
+        self.write_hash(key, |hash| {
+            let mut entry = hash.get_or_insert_mut(field, || Bytes::from_static(b"0"));
+            let current = parse_f64(&entry.value).ok_or_else(|| {
+                CommandError::InvalidArgument("hash value is not a float".to_string())
+            })?;
+            let n = incr_f64(current, delta).ok_or_else(|| {
+                CommandError::InvalidArgument("increment would produce NaN or Infinity".to_string())
+            })?;
+            entry.value = n.into();
+            Ok(entry.value.clone())
+        })?
+    }
+
+    /// HSCAN，按字段哈希值从小到大遍历，游标是下一批的起始哈希值。
+    ///
+    /// 插入或删除不会改变其他字段的顺序，因此整个遍历期间一直存在的字段
+    /// 一定会被返回；返回 0 表示遍历结束。每一步只访问 count + 1 个字段。
+    pub fn hscan(
+        &self,
+        key: &[u8],
+        cursor: u64,
+        count: usize,
+        pattern: Option<&[u8]>,
+    ) -> Result<(u64, FieldValues), CommandError> {
+        Ok(self
+            .read_hash(key, |hash| hash.scan(cursor, count, pattern))?
+            .unwrap_or((0, vec![])))
+    }
+
+    /// HRANDFIELD，count 为正数时返回不重复的字段，为负数时可以重复
+    pub fn hrandfield(&self, key: &[u8], count: i64) -> Result<FieldValues, CommandError> {
+        Ok(self.read_hash(key, |hash| hash.random_fields(count))?.unwrap_or_default())
+    }
+
+    // 读取 hash，key 不存在时返回 None
+    fn read_hash<R>(&self, key: &[u8], f: impl FnOnce(&HashValue) -> R) -> Result<Option<R>, CommandError> {
+        match self.get_entry(key).as_deref().map(|e| &e.value) {
+            Some(RedisValue::Hash(hash)) => Ok(Some(f(hash))),
+            Some(_) => Err(CommandError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // 在 key 的写锁内修改 hash，key 不存在时创建空 hash
+    fn write_hash<R>(&self, key: Bytes, f: impl FnOnce(&mut HashValue) -> R) -> Result<R, CommandError> {
+        let mut entry = self.get_or_insert_with(key, || RedisValue::Hash(HashValue::default()));
+        let (key, entry) = entry.pair_mut();
+        match &mut entry.value {
+            RedisValue::Hash(hash) => {
+                let ret = f(hash);
+                self.unindex_dropped_expires(key, hash.take_dropped_expires());
+                Ok(ret)
+            }
+            _ => Err(CommandError::WrongType),
+        }
+    }
+
+    // 在 key 的写锁内修改已存在的 hash，修改后 hash 为空时删除 key；key 不存在时返回 None
+    fn modify_hash<R>(
+        &self,
+        key: &[u8],
+        f: impl FnOnce(&mut HashValue) -> R,
+    ) -> Result<Option<R>, CommandError> {
+        let mut ret = Ok(None);
+        let removed = self.keyspace.remove_if_mut(key, |key, entry| {
+            if entry.is_expired(now_ms()) {
+                return true;
+            }
+            match &mut entry.value {
+                RedisValue::Hash(hash) => {
+                    ret = Ok(Some(f(hash)));
+                    self.unindex_dropped_expires(key, hash.take_dropped_expires());
+                    hash.is_empty()
+                }
+                _ => {
+                    ret = Err(CommandError::WrongType);
+                    false
+                }
+            }
+        });
+        if let Some((key, entry)) = removed {
+            self.unindex_entry(&key, &entry);
+        }
+        ret
+    }
+}
+
+impl Backend {
+    fn unindex_dropped_expires(&self, key: &[u8], dropped: Vec<(Bytes, i64)>) {
+        for (field, at) in dropped {
+            self.unindex_field_expire(key, &field, Some(at));
+        }
+    }
+
+    /// HEXPIRE 系列：为字段设置过期时间（unix 毫秒），对每个字段返回
+    /// -2 字段不存在，0 条件不满足，1 设置成功，2 时间已过去、字段被删除
+    pub fn hexpire_at(
+        &self,
+        key: &[u8],
+        fields: &[Bytes],
+        at: i64,
+        cond: ExpireCondition,
+    ) -> Result<Vec<i64>, CommandError> {
+        let now = now_ms();
+        let ret = self.modify_hash(key, |hash| {
+            fields
+                .iter()
+                .map(|field| {
+                    let Some(current) = hash.get(field).map(|f| f.expire_at) else {
+                        return -2;
+                    };
+                    if !cond.allows(current, at) {
+                        return 0;
+                    }
+                    self.unindex_field_expire(key, field, current);
+                    if at <= now {
+                        hash.remove(field);
+                        return 2;
+                    }
+                    hash.set_expire(field, Some(at));
+                    self.index_field_expire(key, field, at);
+                    1
+                })
+                .collect()
+        })?;
+        Ok(ret.unwrap_or_else(|| vec![-2; fields.len()]))
+    }
+
+    /// HPERSIST，对每个字段返回 -2 字段不存在，-1 没有过期时间，1 已移除过期时间
+    pub fn hpersist(&self, key: &[u8], fields: &[Bytes]) -> Result<Vec<i64>, CommandError> {
+        let ret = self.modify_hash(key, |hash| {
+            fields
+                .iter()
+                .map(|field| match hash.get(field).map(|f| f.expire_at) {
+                    None => -2,
+                    Some(None) => -1,
+                    Some(current) => {
+                        hash.set_expire(field, None);
+                        self.unindex_field_expire(key, field, current);
+                        1
+                    }
+                })
+                .collect()
+        })?;
+        Ok(ret.unwrap_or_else(|| vec![-2; fields.len()]))
+    }
+
+    /// 字段的过期时间（unix 毫秒），字段不存在为 -2，没有过期时间为 -1
+    pub fn hpexpiretime(&self, key: &[u8], fields: &[Bytes]) -> Result<Vec<i64>, CommandError> {
+        let ret = self.read_hash(key, |hash| {
+            fields
+                .iter()
+                .map(|field| hash.get(field).map_or(-2, |f| f.expire_at.unwrap_or(-1)))
+                .collect()
+        })?;
+        Ok(ret.unwrap_or_else(|| vec![-2; fields.len()]))
+    }
+}
+
+// HSCAN 游标使用的字段哈希，同一进程内必须稳定，因此不能用 RandomState
+fn field_hash(field: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    field.hash(&mut hasher);
+    hasher.finish()
+}
+
+// 部分 Fisher-Yates 洗牌，只打乱前 n 个位置
+fn sample_distinct(mut fields: FieldValues, n: usize) -> FieldValues {
+    let n = n.min(fields.len());
+    for i in 0..n {
+        let j = i + (random_u64() % (fields.len() - i) as u64) as usize;
+        fields.swap(i, j);
+    }
+    fields.truncate(n);
+    fields
+}
+
+fn sample_with_repeats(fields: FieldValues, n: usize) -> FieldValues {
+    if fields.is_empty() {
+        return fields;
+    }
+    (0..n)
+        .map(|_| fields[(random_u64() % fields.len() as u64) as usize].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_hdel_removes_empty_hash() -> Result<()> {
+        let backend = Backend::new();
+        let fields = vec![(Bytes::from_static(b"a"), Bytes::from_static(b"1")), (Bytes::from_static(b"b"), Bytes::from_static(b"22"))];
+        assert_eq!(backend.hset(Bytes::from_static(b"map"), fields)?, 2);
+        assert_eq!(backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"a"), Bytes::from_static(b"3"))])?, 0);
+        assert_eq!(backend.hstrlen(b"map", b"b")?, 2);
+        assert_eq!(backend.hdel(b"map", &[Bytes::from_static(b"a"), Bytes::from_static(b"x")])?, 1);
+        assert!(backend.exists(b"map"));
+        assert_eq!(backend.hdel(b"map", &[Bytes::from_static(b"b")])?, 1);
+        assert!(!backend.exists(b"map"));
+        assert_eq!(backend.hdel(b"map", &[Bytes::from_static(b"b")])?, 0);
+
+        backend.set(Bytes::from_static(b"str"), Bytes::from_static(b"v"));
+        assert!(matches!(backend.hdel(b"str", &[Bytes::from_static(b"a")]), Err(CommandError::WrongType)));
+        assert!(backend.exists(b"str"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_concurrent() -> Result<()> {
+        let backend = Backend::new();
+        let handles = (0..8)
+            .map(|_| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        backend.hincrby(Bytes::from_static(b"map"), Bytes::from_static(b"n"), 1).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(backend.hget(b"map", b"n")?, Some(Bytes::from_static(b"8000")));
+
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"max"), i64::MAX.to_string().into())])?;
+        let err = backend.hincrby(Bytes::from_static(b"map"), Bytes::from_static(b"max"), 1).unwrap_err();
+        assert_eq!(err.to_string(), "increment or decrement would overflow");
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"s"), Bytes::from_static(b"abc"))])?;
+        let err = backend.hincrby(Bytes::from_static(b"map"), Bytes::from_static(b"s"), 1).unwrap_err();
+        assert_eq!(err.to_string(), "hash value is not an integer");
+        let err = backend.hincrbyfloat(Bytes::from_static(b"map"), Bytes::from_static(b"s"), 1.0).unwrap_err();
+        assert_eq!(err.to_string(), "hash value is not a float");
+
+        assert_eq!(backend.hincrbyfloat(Bytes::from_static(b"map"), Bytes::from_static(b"f"), 10.5)?, Bytes::from_static(b"10.5"));
+        assert_eq!(backend.hincrbyfloat(Bytes::from_static(b"map"), Bytes::from_static(b"f"), -0.5)?, Bytes::from_static(b"10"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_with_concurrent_inserts() -> Result<()> {
+        let backend = Backend::new();
+        let fields = (0..100).map(|i| (Bytes::from(format!("f{}", i)), Bytes::from_static(b"v"))).collect();
+        backend.hset(Bytes::from_static(b"map"), fields)?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = 0;
+        let mut round = 0;
+        loop {
+            let (next, items) = backend.hscan(b"map", cursor, 7, None)?;
+            // 遍历过程中插入新字段，不影响已有字段的遍历
+            backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from(format!("new{}", round)), Bytes::from_static(b"v"))])?;
+            round += 1;
+            seen.extend(items.into_iter().map(|(f, _)| f));
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert!((0..100).all(|i| seen.contains(format!("f{}", i).as_bytes())));
+
+        let (_, items) = backend.hscan(b"map", 0, 1000, Some(b"f1?"))?;
+        assert_eq!(items.len(), 10);
+        assert_eq!(backend.hscan(b"none", 0, 10, None)?, (0, vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_with_concurrent_deletes() -> Result<()> {
+        let backend = Backend::new();
+        let fields = (0..1000).map(|i| (Bytes::from(format!("f{}", i)), Bytes::from_static(b"v"))).collect();
+        backend.hset(Bytes::from_static(b"map"), fields)?;
+
+        // 每一步最多返回 count 个字段，删除已返回的字段不影响后续遍历
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let (next, items) = backend.hscan(b"map", cursor, 10, None)?;
+            assert!(items.len() <= 10);
+            let fields = items.into_iter().map(|(f, _)| f).collect::<Vec<_>>();
+            backend.hdel(b"map", &fields)?;
+            assert!(fields.into_iter().all(|f| seen.insert(f)));
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert_eq!(seen.len(), 1000);
+        assert!(!backend.exists(b"map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield() -> Result<()> {
+        let backend = Backend::new();
+        let fields = (0..5).map(|i| (Bytes::from(format!("f{}", i)), Bytes::from_static(b"v"))).collect();
+        backend.hset(Bytes::from_static(b"map"), fields)?;
+
+        let picked = backend.hrandfield(b"map", 3)?;
+        assert_eq!(picked.len(), 3);
+        let distinct: std::collections::HashSet<_> = picked.iter().map(|(f, _)| f).collect();
+        assert_eq!(distinct.len(), 3);
+        assert_eq!(backend.hrandfield(b"map", 10)?.len(), 5);
+        assert_eq!(backend.hrandfield(b"map", -10)?.len(), 10);
+        assert!(backend.hrandfield(b"none", -10)?.is_empty());
+
+        // 字段较多时按下标取样；删除和过期的字段都不会被选中
+        let fields = (0..100).map(|i| (Bytes::from(format!("g{}", i)), Bytes::from(format!("{}", i)))).collect();
+        backend.hset(Bytes::from_static(b"big"), fields)?;
+        let removed = (0..50).map(|i| Bytes::from(format!("g{}", i))).collect::<Vec<_>>();
+        assert_eq!(backend.hdel(b"big", &removed)?, 50);
+        let soon = now_ms() + 20;
+        backend.hexpire_at(b"big", &[Bytes::from_static(b"g50")], soon, ExpireCondition::default())?;
+        std::thread::sleep(std::time::Duration::from_millis(30));
+
+        let picked = backend.hrandfield(b"big", 10)?;
+        let distinct: std::collections::HashSet<_> = picked.iter().map(|(f, _)| f).collect();
+        assert_eq!(distinct.len(), 10);
+        for (field, value) in picked.iter().chain(&backend.hrandfield(b"big", -200)?) {
+            assert_eq!(field[1..], value[..]);
+            assert!(value.as_ref() > b"50".as_slice());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_expire() -> Result<()> {
+        let backend = Backend::new();
+        let fields = vec![(Bytes::from_static(b"a"), Bytes::from_static(b"1")), (Bytes::from_static(b"b"), Bytes::from_static(b"2"))];
+        backend.hset(Bytes::from_static(b"map"), fields)?;
+        let future = now_ms() + 100_000;
+        let names = [Bytes::from_static(b"a"), Bytes::from_static(b"x")];
+        assert_eq!(backend.hexpire_at(b"map", &names, future, ExpireCondition::default())?, vec![1, -2]);
+        assert_eq!(backend.hpexpiretime(b"map", &names)?, vec![future, -2]);
+        let nx = ExpireCondition { nx: true, ..Default::default() };
+        assert_eq!(backend.hexpire_at(b"map", &names[..1], future, nx)?, vec![0]);
+        assert_eq!(backend.hpersist(b"map", &[Bytes::from_static(b"a"), Bytes::from_static(b"b")])?, vec![1, -1]);
+        assert_eq!(backend.hexpire_at(b"none", &names, future, nx)?, vec![-2, -2]);
+
+        // 过去的时间直接删除字段，最后一个字段被删除时 key 也被删除
+        let past = now_ms() - 1;
+        assert_eq!(backend.hexpire_at(b"map", &[Bytes::from_static(b"a")], past, nx)?, vec![2]);
+        assert_eq!(backend.hlen(b"map")?, 1);
+        assert_eq!(backend.hexpire_at(b"map", &[Bytes::from_static(b"b")], past, nx)?, vec![2]);
+        assert!(!backend.exists(b"map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_fields_are_invisible_and_reclaimed() -> Result<()> {
+        let backend = Backend::new();
+        let fields = vec![(Bytes::from_static(b"a"), Bytes::from_static(b"1")), (Bytes::from_static(b"b"), Bytes::from_static(b"2"))];
+        backend.hset(Bytes::from_static(b"map"), fields)?;
+        let soon = now_ms() + 20;
+        backend.hexpire_at(b"map", &[Bytes::from_static(b"a")], soon, ExpireCondition::default())?;
+        std::thread::sleep(std::time::Duration::from_millis(30));
+
+        assert_eq!(backend.hget(b"map", b"a")?, None);
+        assert_eq!(backend.hlen(b"map")?, 1);
+        assert_eq!(backend.hgetall(b"map")?, vec![(Bytes::from_static(b"b"), Bytes::from_static(b"2"))]);
+        // 过期字段可以被重新写入，HINCRBY 从 0 开始
+        assert_eq!(backend.hincrby(Bytes::from_static(b"map"), Bytes::from_static(b"a"), 5)?, 5);
+        assert_eq!(backend.hpexpiretime(b"map", &[Bytes::from_static(b"a")])?, vec![-1]);
+
+        let soon = now_ms() + 20;
+        backend.hexpire_at(b"map", &[Bytes::from_static(b"a"), Bytes::from_static(b"b")], soon, ExpireCondition::default())?;
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert_eq!(backend.active_expire_cycle(), 2);
+        assert!(!backend.exists(b"map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_expires_follow_rename() -> Result<()> {
+        let backend = Backend::new();
+        let far = now_ms() + 100_000;
+        backend.hset(Bytes::from_static(b"dest"), vec![(Bytes::from_static(b"x"), Bytes::from_static(b"1"))])?;
+        backend.hexpire_at(b"dest", &[Bytes::from_static(b"x")], far, ExpireCondition::default())?;
+        let fields = vec![(Bytes::from_static(b"a"), Bytes::from_static(b"1")), (Bytes::from_static(b"b"), Bytes::from_static(b"2"))];
+        backend.hset(Bytes::from_static(b"map"), fields.clone())?;
+        backend.hset(Bytes::from_static(b"other"), fields)?;
+        let soon = now_ms() + 20;
+        let names = [Bytes::from_static(b"a"), Bytes::from_static(b"b")];
+        backend.hexpire_at(b"map", &names, soon, ExpireCondition::default())?;
+        backend.hexpire_at(b"other", &names, soon, ExpireCondition::default())?;
+
+        // 覆盖 dest 时，旧值遗留的字段过期索引项也被清除
+        backend.rename(b"map", Bytes::from_static(b"dest"))?;
+        assert!(backend.renamenx(b"other", Bytes::from_static(b"fresh"))?);
+        {
+            let expires = backend.field_expires.lock().unwrap();
+            assert_eq!(expires.len(), 4);
+            assert!(expires.iter().all(|(at, key, _)| *at == soon && (key == "dest" || key == "fresh")));
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert_eq!(backend.active_expire_cycle(), 4);
+        assert!(!backend.exists(b"dest"));
+        assert!(!backend.exists(b"fresh"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_expires_are_unindexed_on_every_removal() -> Result<()> {
+        let backend = Backend::new();
+        let far = now_ms() + 100_000;
+        let (map, a, b) = (Bytes::from_static(b"map"), Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+        let indexed = || backend.field_expires.lock().unwrap().len();
+        let setup = || -> Result<()> {
+            backend.hset(map.clone(), vec![(a.clone(), Bytes::from_static(b"1")), (b.clone(), Bytes::from_static(b"2"))])?;
+            backend.hexpire_at(&map, &[a.clone(), b.clone()], far, ExpireCondition::default())?;
+            Ok(())
+        };
+
+        // HSET 覆盖字段会清除它的过期时间
+        setup()?;
+        backend.hset(map.clone(), vec![(a.clone(), Bytes::from_static(b"3"))])?;
+        assert_eq!(indexed(), 1);
+        // HINCRBY 不会改变过期时间，HDEL 删除字段
+        backend.hincrby(map.clone(), b.clone(), 1)?;
+        assert_eq!(indexed(), 1);
+        backend.hdel(&map, std::slice::from_ref(&b))?;
+        assert_eq!(indexed(), 0);
+
+        // DEL 删除整个 key
+        setup()?;
+        assert!(backend.del(&map));
+        assert_eq!(indexed(), 0);
+
+        // SET 覆盖 hash
+        setup()?;
+        backend.set(map.clone(), Bytes::from_static(b"v"));
+        assert_eq!(indexed(), 0);
+        backend.del(&map);
+
+        // key 过期：惰性删除和主动过期
+        setup()?;
+        backend.keyspace.get_mut(&map).unwrap().expire_at = Some(1);
+        assert_eq!(backend.hlen(&map)?, 0);
+        assert_eq!(indexed(), 0);
+        setup()?;
+        assert!(backend.expire_at(&map, far, ExpireCondition::default()));
+        backend.keyspace.get_mut(&map).unwrap().expire_at = Some(1);
+        backend.index_expire(&map, Some(1));
+        assert_eq!(backend.active_expire_cycle(), 1);
+        assert_eq!(indexed(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hsetnx_hmget() -> Result<()> {
+        let backend = Backend::new();
+        assert!(backend.hsetnx(Bytes::from_static(b"map"), Bytes::from_static(b"a"), Bytes::from_static(b"1"))?);
+        assert!(!backend.hsetnx(Bytes::from_static(b"map"), Bytes::from_static(b"a"), Bytes::from_static(b"2"))?);
+        assert_eq!(
+            backend.hmget(b"map", &[Bytes::from_static(b"a"), Bytes::from_static(b"b")])?,
+            vec![Some(Bytes::from_static(b"1")), None]
+        );
+        assert_eq!(backend.hmget(b"none", &[Bytes::from_static(b"a")])?, vec![None]);
+        Ok(())
+    }
+}
@@ -1,17 +1,80 @@
+mod bitmap;
+mod expire;
+mod glob;
+mod hash;
+mod string;
+
+use std::collections::hash_map::RandomState;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::hash::{BuildHasher, Hasher};
 use std::ops::Deref;
-use std::sync::Arc;
-use dashmap::DashMap;
-use crate::RespFrame;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::{DashMap, SharedValue};
+use crate::cmd::CommandError;
+
+pub use bitmap::{
+    bitcount, bitpos,
+    BitFieldOp, BitFieldOverflow, BitFieldType, BitOpKind, BitUnit, MAX_BIT_OFFSET,
+};
+pub use expire::{now_ms, ExpireCondition, ExpireUpdate};
+pub use glob::glob_match;
+pub use hash::{FieldValues, HashField, HashValue};
+pub use string::{
+    format_f64, incr_f64, lcs, parse_f64, parse_i64, LcsMatch, SetCondition, StringValue, MAX_STRING_LEN,
+};
 
 #[derive(Debug,Clone)]
 pub struct Backend(Arc<BackendInner>);
 
+/// keyspace 中保存的值，每个 key 只对应一种类型
+#[derive(Debug)]
+pub enum RedisValue {
+    String(StringValue),
+    Hash(HashValue),
+    // List / Set / ZSet / Stream 之后在这里扩展
+}
 
+impl RedisValue {
+    /// TYPE 命令返回的类型名
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::Hash(_) => "hash",
+        }
+    }
+}
+
+/// keyspace 中的一项：值以及可选的过期时间（unix 毫秒）
+#[derive(Debug)]
+pub struct ValueEntry {
+    pub(crate) value: RedisValue,
+    pub(crate) expire_at: Option<i64>,
+}
+
+impl ValueEntry {
+    pub fn new(value: RedisValue) -> Self {
+        Self { value, expire_at: None }
+    }
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire_at.is_some_and(|at| at <= now)
+    }
+}
 
 #[derive(Debug)]
 pub struct BackendInner{
-    pub(crate) map:DashMap<String,RespFrame>,
-    pub(crate) hmap:DashMap<String,DashMap<String,RespFrame>>
+    pub(crate) keyspace:DashMap<Bytes,ValueEntry>,
+    // 带过期时间的 key，相当于 Redis 的 db->expires，供后台主动过期任务抽样；
+    // 与 keyspace 一样分片加锁，以 keyspace 中的 expire_at 为准
+    pub(crate) expires:DashMap<Bytes,i64>,
+    // 主动过期扫描 expires 的游标，见 expire.rs
+    pub(crate) expire_cursor:AtomicU64,
+    // hash 字段的过期索引：(过期时间, key, 字段)
+    pub(crate) field_expires:Mutex<BTreeSet<(i64,Bytes,Bytes)>>,
 }
 impl Deref for Backend{
     type Target = BackendInner;
@@ -27,8 +90,10 @@ impl Default for Backend{
 impl Default for BackendInner{
     fn default() -> Self {
         Self{
-            map:DashMap::new(),
-            hmap: DashMap::new()
+            keyspace:DashMap::new(),
+            expires:DashMap::new(),
+            expire_cursor:AtomicU64::new(0),
+            field_expires:Mutex::new(BTreeSet::new()),
         }
     }
 }
@@ -36,20 +101,188 @@ impl Backend {
     pub fn new()->Self{
         Self::default()
     }
-    pub fn get(&self,key:&str)->Option<RespFrame>{
-        self.map.get(key).map(|v|v.value().clone())
+    pub fn get(&self,key:&[u8])->Result<Option<Bytes>,CommandError>{
+        match self.get_entry(key).as_deref().map(|e| &e.value) {
+            Some(RedisValue::String(v)) => Ok(Some(v.to_bytes())),
+            Some(_) => Err(CommandError::WrongType),
+            None => Ok(None),
+        }
+    }
+    // SET 会覆盖任意类型的旧值，并清除过期时间
+    pub fn set(&self,key:Bytes,value:Bytes){
+        // 不带 GET 时不会返回错误
+        let _ = self.set_with(key, value, SetCondition::Always, ExpireUpdate::Clear, false);
+    }
+    pub fn key_type(&self,key:&[u8])->&'static str{
+        self.get_entry(key).map_or("none", |e| e.value.type_name())
+    }
+    pub fn del(&self,key:&[u8])->bool{
+        match self.keyspace.remove(key) {
+            Some((key, entry)) => {
+                self.unindex_entry(&key, &entry);
+                !entry.is_expired(now_ms())
+            }
+            None => false,
+        }
+    }
+    pub fn exists(&self,key:&[u8])->bool{
+        self.get_entry(key).is_some()
+    }
+    pub fn rename(&self,key:&[u8],newkey:Bytes)->Result<(),CommandError>{
+        self.rename_locked(key, newkey, false).map(|_| ())
+    }
+    pub fn renamenx(&self,key:&[u8],newkey:Bytes)->Result<bool,CommandError>{
+        self.rename_locked(key, newkey, true)
+    }
+    pub fn dbsize(&self)->usize{
+        self.keyspace.len()
     }
-    pub fn set(&self,key:String,value:RespFrame){
-        self.map.insert(key,value);
+    // 先随机选一个分片，再从分片内的随机槽位向后找第一个有值的槽位，
+    // 与 Redis 的 dictGetRandomKey 类似，不需要遍历整个 keyspace
+    pub fn random_key(&self)->Option<Bytes>{
+        let shards = self.keyspace.shards();
+        loop {
+            let start = random_u64() as usize % shards.len();
+            let key = (0..shards.len()).find_map(|i| {
+                let shard = shards[(start + i) % shards.len()].read();
+                if shard.is_empty() {
+                    return None;
+                }
+                let buckets = shard.buckets();
+                let offset = random_u64() as usize % buckets;
+                let index = (0..buckets)
+                    .map(|i| (offset + i) % buckets)
+                    // SAFETY: 持有分片的读锁，下标对 buckets() 取模，不会越界
+                    .find(|&i| unsafe { shard.is_bucket_full(i) })?;
+                // SAFETY: 下标合法且槽位已占用；读锁保证槽位在克隆 key 之前不会被移除或释放
+                let (key, _) = unsafe { shard.bucket(index).as_ref() };
+                Some(key.clone())
+            })?;
+            // 抽到已过期的 key 时顺便删除，然后重新抽取
+            if self.exists(&key) {
+                return Some(key);
+            }
+        }
+    }
+
+    /// 读取 key，已过期的 key 会被惰性删除并视为不存在
+    pub(crate) fn get_entry(&self,key:&[u8])->Option<Ref<'_, Bytes,ValueEntry>>{
+        let entry = self.keyspace.get(key)?;
+        if !entry.is_expired(now_ms()) {
+            return Some(entry);
+        }
+        drop(entry);
+        self.remove_expired(key);
+        None
     }
-    pub fn hget(&self,key:&str,field:&str)->Option<RespFrame>{
-        self.hmap.get(key).and_then(|v|v.get(field).map(|v|v.value().clone()))
+    pub(crate) fn get_entry_mut(&self,key:&[u8])->Option<RefMut<'_, Bytes,ValueEntry>>{
+        let entry = self.keyspace.get_mut(key)?;
+        if !entry.is_expired(now_ms()) {
+            return Some(entry);
+        }
+        drop(entry);
+        self.remove_expired(key);
+        None
     }
-    pub fn hset(&self,key:String,field:String,value:RespFrame){
-        let hmap = self.hmap.entry(key).or_insert_with(DashMap::new);
-        hmap.insert(field,value);
+    /// 取出 key 对应的项以便写入，不存在或已过期时用 `f` 创建新值
+    pub(crate) fn get_or_insert_with(
+        &self,
+        key:Bytes,
+        f:impl FnOnce()->RedisValue,
+    )->RefMut<'_, Bytes,ValueEntry>{
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut occupied) => {
+                if occupied.get().is_expired(now_ms()) {
+                    self.unindex_entry(occupied.key(), occupied.get());
+                    occupied.insert(ValueEntry::new(f()));
+                }
+                occupied.into_ref()
+            }
+            Entry::Vacant(vacant) => vacant.insert(ValueEntry::new(f())),
+        }
     }
-    pub fn hgetall(&self,key:&str)->Option<DashMap<String,RespFrame>>{
-        self.hmap.get(key).map(|v|v.clone())
+    // 同时持有 key 和 newkey 所在分片的写锁完成移动，其他客户端要么看到改名前的 key，
+    // 要么看到改名后的 key。两个分片按下标从小到大加锁，避免反方向的 RENAME 互相等待；
+    // 过期索引的锁在分片锁之后获取，与其他写路径的顺序一致
+    fn rename_locked(&self,key:&[u8],newkey:Bytes,nx:bool)->Result<bool,CommandError>{
+        let hasher = self.keyspace.hasher();
+        let ((hash, from), (newhash, to)) = (self.locate(key), self.locate(&newkey));
+        let shards = self.keyspace.shards();
+        let (mut src, mut dst) = match from.cmp(&to) {
+            Ordering::Equal => (shards[from].write(), None),
+            Ordering::Less => {
+                let src = shards[from].write();
+                (src, Some(shards[to].write()))
+            }
+            Ordering::Greater => {
+                let dst = shards[to].write();
+                (shards[from].write(), Some(dst))
+            }
+        };
+        let now = now_ms();
+        match src.get(hash, is_key(key)) {
+            None => return Err(no_such_key()),
+            Some((_, entry)) if entry.get().is_expired(now) => {
+                if let Some((key, entry)) = src.remove_entry(hash, is_key(key)) {
+                    self.unindex_entry(&key, entry.get());
+                }
+                return Err(no_such_key());
+            }
+            Some(_) => {}
+        }
+        if key == newkey {
+            return Ok(!nx);
+        }
+        if nx {
+            let table = dst.as_deref().unwrap_or(&*src);
+            if table.get(newhash, is_key(&newkey)).is_some_and(|(_, e)| !e.get().is_expired(now)) {
+                return Ok(false);
+            }
+        }
+        let Some((key, entry)) = src.remove_entry(hash, is_key(key)) else {
+            return Err(no_such_key());
+        };
+        let table = match dst.as_deref_mut() {
+            Some(table) => table,
+            None => &mut *src,
+        };
+        if let Some((_, old)) = table.remove_entry(newhash, is_key(&newkey)) {
+            self.unindex_entry(&newkey, old.get());
+        }
+        self.unindex_expire(&key, entry.get().expire_at);
+        self.index_expire(&newkey, entry.get().expire_at);
+        self.move_field_expires(&key, &newkey, &entry.get().value);
+        table.insert(newhash, (newkey, entry), |(k, _)| hasher.hash_one(k));
+        Ok(true)
     }
-}
\ No newline at end of file
+}
+
+impl Backend {
+    // key 的哈希值以及所在的分片下标，与 DashMap 内部的计算方式一致
+    pub(crate) fn locate(&self,key:&[u8])->(u64,usize){
+        let hash = self.keyspace.hasher().hash_one(key);
+        (hash, self.keyspace.determine_shard(hash as usize))
+    }
+}
+
+// 多 key 命令需要锁住的分片：去重后按下标从小到大排列，与 rename_locked 的加锁顺序一致
+fn lock_order(located:&[(u64,usize)])->Vec<usize>{
+    let mut order = located.iter().map(|&(_, shard)| shard).collect::<Vec<_>>();
+    order.sort_unstable();
+    order.dedup();
+    order
+}
+
+// 在分片的哈希表中按 key 查找
+fn is_key(key:&[u8])->impl Fn(&(Bytes,SharedValue<ValueEntry>))->bool+'_{
+    move |(k, _)| k.as_ref() == key
+}
+
+fn no_such_key() -> CommandError {
+    CommandError::InvalidArgument("no such key".to_string())
+}
+
+// 不引入 rand，借用 RandomState 的随机种子
+pub(crate) fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
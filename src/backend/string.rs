@@ -0,0 +1,732 @@
+use std::hash::BuildHasher;
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use dashmap::SharedValue;
+use crate::backend::{is_key, lock_order, now_ms, Backend, ExpireUpdate, RedisValue, ValueEntry};
+use crate::cmd::CommandError;
+
+// 可以按整数编码的最长字符串，与 Redis 一致
+const MAX_INT_STR_LEN: usize = 20;
+// 字符串的最大长度（512MB），与 Redis 的 proto-max-bulk-len 默认值一致
+pub const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// 字符串值：二进制安全的字节，能无损表示为 i64 的值直接按整数保存。
+///
+/// 字节使用引用计数的 `Bytes`，读取时只增加引用计数，不复制数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringValue {
+    Raw(Bytes),
+    Int(i64),
+}
+
+impl StringValue {
+    pub fn new(bytes: Bytes) -> Self {
+        match parse_i64(&bytes) {
+            Some(n) => StringValue::Int(n),
+            None => StringValue::Raw(bytes),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        match self {
+            StringValue::Raw(bytes) => bytes.clone(),
+            StringValue::Int(n) => n.to_string().into(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        match self {
+            StringValue::Raw(bytes) => bytes,
+            StringValue::Int(n) => n.to_string().into(),
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            StringValue::Raw(bytes) => parse_i64(bytes),
+            StringValue::Int(n) => Some(*n),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            StringValue::Raw(bytes) => parse_f64(bytes),
+            StringValue::Int(n) => Some(*n as f64),
+        }
+    }
+}
+
+/// 严格解析整数：不允许前导 '+'、前导零和空白，与 Redis 的 string2ll 一致
+pub fn parse_i64(bytes: &[u8]) -> Option<i64> {
+    if bytes.is_empty() || bytes.len() > MAX_INT_STR_LEN {
+        return None;
+    }
+    let digits = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    match digits {
+        [b'0'] if digits.len() == bytes.len() => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {
+            std::str::from_utf8(bytes).ok()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// 解析浮点数，不接受空白、NaN 与无穷大
+pub fn parse_f64(bytes: &[u8]) -> Option<f64> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    if s.is_empty() || s.starts_with(char::is_whitespace) || s.ends_with(char::is_whitespace) {
+        return None;
+    }
+    s.parse::<f64>().ok().filter(|f| f.is_finite())
+}
+
+/// 浮点数转字符串，整数值不带小数部分
+pub fn format_f64(f: f64) -> String {
+    format!("{}", f)
+}
+
+// INCRBYFLOAT 结果保留的有效数字位数，与 Redis 的 %.17Lg 一致
+const INCR_FLOAT_DIGITS: usize = 17;
+
+/// INCRBYFLOAT / HINCRBYFLOAT 的加法，返回写入的字符串；结果为 NaN 或无穷大时返回 None。
+///
+/// Redis 用 long double 相加，保留 17 位有效数字并去掉末尾的 0，所以 0.1 + 0.2 得到 "0.3"。
+/// f64 直接相加会得到 0.30000000000000004，因此这里把两个数的最短十进制形式按十进制精确相加，
+/// 位数超出 i128 时才退回 f64 的和
+pub fn incr_f64(current: f64, delta: f64) -> Option<String> {
+    let sum = current + delta;
+    if !sum.is_finite() {
+        return None;
+    }
+    let (negative, digits, exp) = match (to_decimal(current), to_decimal(delta)) {
+        (Some((a, a_scale)), Some((b, b_scale))) => {
+            let scale = a_scale.max(b_scale);
+            let align = |n: i128, s: u32| 10i128.checked_pow(scale - s).and_then(|p| n.checked_mul(p));
+            match align(a, a_scale).zip(align(b, b_scale)).and_then(|(a, b)| a.checked_add(b)) {
+                Some(n) => (n < 0, n.unsigned_abs().to_string(), -(scale as i32)),
+                None => f64_digits(sum),
+            }
+        }
+        _ => f64_digits(sum),
+    };
+    Some(render_decimal(negative, digits, exp))
+}
+
+// 把 f64 的最短十进制形式转为 (整数, 小数位数)，数值为 整数 / 10^小数位数
+fn to_decimal(f: f64) -> Option<(i128, u32)> {
+    let s = format!("{}", f.abs());
+    let (int, frac) = s.split_once('.').unwrap_or((&s, ""));
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    // i128 最多容纳 38 位十进制数字
+    if digits.len() > 38 {
+        return None;
+    }
+    let n = if digits.is_empty() { 0 } else { digits.parse::<i128>().ok()? };
+    Some((if f < 0.0 { -n } else { n }, frac.len() as u32))
+}
+
+// f64 的最短十进制数字以及对应的 10 的幂，数值为 数字 * 10^幂
+fn f64_digits(f: f64) -> (bool, String, i32) {
+    let s = format!("{:e}", f.abs());
+    let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+    let digits = mantissa.replace('.', "");
+    let exp = exp.parse::<i32>().unwrap_or(0) - (digits.len() as i32 - 1);
+    (f < 0.0, digits, exp)
+}
+
+// 数字 * 10^exp 保留 17 位有效数字（四舍五入），按定点形式输出并去掉末尾的 0
+fn render_decimal(negative: bool, digits: String, mut exp: i32) -> String {
+    let mut digits = digits.trim_start_matches('0').as_bytes().to_vec();
+    if digits.len() > INCR_FLOAT_DIGITS {
+        let round_up = digits[INCR_FLOAT_DIGITS] >= b'5';
+        exp += (digits.len() - INCR_FLOAT_DIGITS) as i32;
+        digits.truncate(INCR_FLOAT_DIGITS);
+        if round_up {
+            match digits.iter().rposition(|&d| d != b'9') {
+                Some(i) => {
+                    digits[i] += 1;
+                    digits.truncate(i + 1);
+                    exp += (INCR_FLOAT_DIGITS - i - 1) as i32;
+                }
+                // 全是 9，进位成 1 后面跟 0
+                None => {
+                    exp += digits.len() as i32;
+                    digits = vec![b'1'];
+                }
+            }
+        }
+    }
+    while digits.len() > 1 && digits.last() == Some(&b'0') {
+        digits.pop();
+        exp += 1;
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    let digits = String::from_utf8(digits).expect("ascii digits");
+    let sign = if negative { "-" } else { "" };
+    if exp >= 0 {
+        return format!("{}{}{}", sign, digits, "0".repeat(exp as usize));
+    }
+    let frac_len = exp.unsigned_abs() as usize;
+    match digits.len().checked_sub(frac_len) {
+        Some(0) => format!("{}0.{}", sign, digits),
+        Some(int_len) => format!("{}{}.{}", sign, &digits[..int_len], &digits[int_len..]),
+        None => format!("{}0.{}{}", sign, "0".repeat(frac_len - digits.len()), digits),
+    }
+}
+
+/// SET 的 NX / XX 选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    // 仅当 key 不存在时设置
+    Nx,
+    // 仅当 key 已存在时设置
+    Xx,
+}
+
+impl Backend {
+    /// 带选项的 SET，返回 (是否写入, 旧值)。
+    ///
+    /// `get` 为 true 时旧值必须是字符串，否则返回 WRONGTYPE 且不写入。
+    pub fn set_with(
+        &self,
+        key: Bytes,
+        value: Bytes,
+        cond: SetCondition,
+        expire: ExpireUpdate,
+        get: bool,
+    ) -> Result<(bool, Option<Bytes>), CommandError> {
+        let value = RedisValue::String(StringValue::new(value));
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut occupied) if !occupied.get().is_expired(now_ms()) => {
+                let old = match &occupied.get().value {
+                    RedisValue::String(v) => Some(v.to_bytes()),
+                    _ if get => return Err(CommandError::WrongType),
+                    _ => None,
+                };
+                if cond == SetCondition::Nx {
+                    return Ok((false, old));
+                }
+                let key = occupied.key().clone();
+                let entry = occupied.get_mut();
+                // 覆盖 hash 时，它的字段过期索引项随旧值一起作废
+                self.unindex_field_expires(&key, &entry.value);
+                entry.value = value;
+                self.update_expire(&key, entry, expire);
+                Ok((true, old))
+            }
+            Entry::Occupied(mut occupied) => {
+                // 已过期的 key 视为不存在
+                self.unindex_entry(occupied.key(), occupied.get());
+                if cond == SetCondition::Xx {
+                    occupied.remove();
+                    return Ok((false, None));
+                }
+                let key = occupied.key().clone();
+                let entry = occupied.get_mut();
+                *entry = ValueEntry::new(value);
+                self.update_expire(&key, entry, expire);
+                Ok((true, None))
+            }
+            Entry::Vacant(vacant) => {
+                if cond == SetCondition::Xx {
+                    return Ok((false, None));
+                }
+                let key = vacant.key().clone();
+                let mut entry = vacant.insert(ValueEntry::new(value));
+                self.update_expire(&key, &mut entry, expire);
+                Ok((true, None))
+            }
+        }
+    }
+
+    /// 读取并删除字符串
+    pub fn getdel(&self, key: &[u8]) -> Result<Option<Bytes>, CommandError> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(None);
+        };
+        if !matches!(entry.value, RedisValue::String(_)) {
+            return Err(CommandError::WrongType);
+        }
+        drop(entry);
+        // 读取与删除之间值可能被改写，以删除时拿到的值为准
+        match self.keyspace.remove_if(key, |_, e| matches!(e.value, RedisValue::String(_))) {
+            Some((key, entry)) => {
+                self.unindex_expire(&key, entry.expire_at);
+                let expired = entry.is_expired(now_ms());
+                match entry.value {
+                    RedisValue::String(v) if !expired => Ok(Some(v.into_bytes())),
+                    _ => Ok(None),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 读取字符串并更新过期时间，过期时间已经过去时读取后删除 key
+    pub fn getex(&self, key: &[u8], expire: ExpireUpdate) -> Result<Option<Bytes>, CommandError> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(None);
+        };
+        let value = match &entry.value {
+            RedisValue::String(v) => v.to_bytes(),
+            _ => return Err(CommandError::WrongType),
+        };
+        match expire {
+            ExpireUpdate::At(at) if at <= now_ms() => {
+                drop(entry);
+                self.del(key);
+            }
+            expire => self.update_expire(key, &mut entry, expire),
+        }
+        Ok(Some(value))
+    }
+
+    /// INCRBY / DECRBY：在 key 的写锁内完成读取、计算和写回，并发客户端之间是原子的。
+    /// 不存在的 key 视为 0，过期时间保持不变。
+    pub fn incr_by(&self, key: &[u8], delta: i64) -> Result<i64, CommandError> {
+        let mut entry = self.get_or_insert_with(Bytes::copy_from_slice(key), || {
+            RedisValue::String(StringValue::Int(0))
+        });
+        let RedisValue::String(value) = &mut entry.value else {
+            return Err(CommandError::WrongType);
+        };
+        let current = value.as_i64().ok_or(CommandError::NotInteger)?;
+        let n = current.checked_add(delta).ok_or_else(|| {
+            CommandError::InvalidArgument("increment or decrement would overflow".to_string())
+        })?;
+        *value = StringValue::Int(n);
+        Ok(n)
+    }
+
+    /// INCRBYFLOAT，返回写入后的字符串形式
+    pub fn incr_by_float(&self, key: &[u8], delta: f64) -> Result<Bytes, CommandError> {
+        let mut entry = self.get_or_insert_with(Bytes::copy_from_slice(key), || {
+            RedisValue::String(StringValue::Int(0))
+        });
+        let RedisValue::String(value) = &mut entry.value else {
+            return Err(CommandError::WrongType);
+        };
+        let current = value.as_f64().ok_or(CommandError::NotFloat)?;
+        let n = incr_f64(current, delta).ok_or_else(|| {
+            CommandError::InvalidArgument("increment would produce NaN or Infinity".to_string())
+        })?;
+        let bytes = Bytes::from(n);
+        *value = StringValue::new(bytes.clone());
+        Ok(bytes)
+    }
+}
+
+impl Backend {
+    /// APPEND，返回追加后的长度；key 不存在时等同于 SET
+    pub fn append(&self, key: &[u8], suffix: &[u8]) -> Result<usize, CommandError> {
+        // 先检查，key 不存在时不会因为失败的 APPEND 留下一个空串
+        check_string_len(suffix.len())?;
+        self.modify_string(key, |bytes| {
+            check_string_len(bytes.len() + suffix.len())?;
+            bytes.extend_from_slice(suffix);
+            Ok(bytes.len())
+        })?
+    }
+
+    pub fn strlen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        match self.get_entry(key).as_deref().map(|e| &e.value) {
+            Some(RedisValue::String(StringValue::Raw(bytes))) => Ok(bytes.len()),
+            Some(RedisValue::String(StringValue::Int(n))) => Ok(n.to_string().len()),
+            Some(_) => Err(CommandError::WrongType),
+            None => Ok(0),
+        }
+    }
+
+    /// GETRANGE，start / end 为闭区间，负数表示从末尾倒数
+    pub fn getrange(&self, key: &[u8], start: i64, end: i64) -> Result<Bytes, CommandError> {
+        let bytes = self.get(key)?.unwrap_or_default();
+        let len = bytes.len() as i64;
+        if start < 0 && end < 0 && start > end {
+            return Ok(Bytes::new());
+        }
+        let start = if start < 0 { (len + start).max(0) } else { start };
+        let end = if end < 0 { (len + end).max(0) } else { end.min(len - 1) };
+        if len == 0 || start > end {
+            return Ok(Bytes::new());
+        }
+        Ok(bytes.slice(start as usize..=end as usize))
+    }
+
+    /// SETRANGE，返回修改后的长度，中间空出的部分以 0 填充
+    pub fn setrange(&self, key: &[u8], offset: usize, patch: &[u8]) -> Result<usize, CommandError> {
+        // 写入空串时不创建 key
+        if patch.is_empty() {
+            return self.strlen(key);
+        }
+        check_string_len(offset + patch.len())?;
+        self.modify_string(key, |bytes| {
+            if bytes.len() < offset + patch.len() {
+                bytes.resize(offset + patch.len(), 0);
+            }
+            bytes[offset..offset + patch.len()].copy_from_slice(patch);
+            bytes.len()
+        })
+    }
+
+    /// 在 key 的写锁内修改字符串的字节，key 不存在时从空串开始
+    pub(crate) fn modify_string<R>(
+        &self,
+        key: &[u8],
+        f: impl FnOnce(&mut Vec<u8>) -> R,
+    ) -> Result<R, CommandError> {
+        let mut entry = self.get_or_insert_with(Bytes::copy_from_slice(key), || {
+            RedisValue::String(StringValue::Raw(Bytes::new()))
+        });
+        let RedisValue::String(value) = &mut entry.value else {
+            return Err(CommandError::WrongType);
+        };
+        // 没有其他引用时转换为 Vec 不会复制
+        let mut bytes = Vec::from(std::mem::replace(value, StringValue::Int(0)).into_bytes());
+        let ret = f(&mut bytes);
+        *value = StringValue::Raw(bytes.into());
+        Ok(ret)
+    }
+}
+
+impl Backend {
+    /// MGET，不存在或不是字符串的 key 返回 None
+    pub fn mget(&self, keys: &[Bytes]) -> Vec<Option<Bytes>> {
+        self.get_many(keys).into_iter().map(|v| v.ok().flatten()).collect()
+    }
+
+    /// 同时读取多个字符串（MGET / LCS / BITOP），读取期间持有这些 key 所在分片的读锁，
+    /// 因此不会看到只写了一半的 MSET
+    pub fn get_many(&self, keys: &[Bytes]) -> Vec<Result<Option<Bytes>, CommandError>> {
+        let located = keys.iter().map(|key| self.locate(key)).collect::<Vec<_>>();
+        let order = lock_order(&located);
+        let shards = self.keyspace.shards();
+        let guards = order.iter().map(|&i| shards[i].read()).collect::<Vec<_>>();
+        let now = now_ms();
+        keys.iter()
+            .zip(located)
+            .map(|(key, (hash, shard))| {
+                let table = &guards[order.binary_search(&shard).unwrap()];
+                // 已过期的 key 视为不存在，留给惰性删除和主动过期回收
+                match table.get(hash, is_key(key)).map(|(_, e)| e.get()) {
+                    Some(entry) if !entry.is_expired(now) => match &entry.value {
+                        RedisValue::String(v) => Ok(Some(v.to_bytes())),
+                        _ => Err(CommandError::WrongType),
+                    },
+                    _ => Ok(None),
+                }
+            })
+            .collect()
+    }
+
+    /// MSET，写入期间持有所有 key 所在分片的写锁，其他命令要么看到全部旧值，要么看到全部新值
+    pub fn mset(&self, pairs: Vec<(Bytes, Bytes)>) {
+        self.set_many(pairs, false);
+    }
+
+    /// MSETNX，只要有一个 key 已存在就什么都不写，返回是否写入
+    pub fn msetnx(&self, pairs: Vec<(Bytes, Bytes)>) -> bool {
+        self.set_many(pairs, true)
+    }
+
+    // 与 SET 相同，覆盖任意类型的旧值并清除过期时间；检查和写入在同一组分片锁内完成
+    fn set_many(&self, pairs: Vec<(Bytes, Bytes)>, nx: bool) -> bool {
+        let located = pairs.iter().map(|(key, _)| self.locate(key)).collect::<Vec<_>>();
+        let order = lock_order(&located);
+        let shards = self.keyspace.shards();
+        let mut guards = order.iter().map(|&i| shards[i].write()).collect::<Vec<_>>();
+        let index = |shard: usize| order.binary_search(&shard).unwrap();
+        let now = now_ms();
+        let exists = pairs.iter().zip(&located).any(|((key, _), &(hash, shard))| {
+            let entry = guards[index(shard)].get(hash, is_key(key));
+            entry.is_some_and(|(_, e)| !e.get().is_expired(now))
+        });
+        if nx && exists {
+            return false;
+        }
+        let hasher = self.keyspace.hasher();
+        for ((key, value), (hash, shard)) in pairs.into_iter().zip(located) {
+            let table = &mut guards[index(shard)];
+            if let Some((_, old)) = table.remove_entry(hash, is_key(&key)) {
+                self.unindex_entry(&key, old.get());
+            }
+            let entry = ValueEntry::new(RedisValue::String(StringValue::new(value)));
+            table.insert(hash, (key, SharedValue::new(entry)), |(k, _)| hasher.hash_one(k));
+        }
+        true
+    }
+}
+
+fn check_string_len(len: usize) -> Result<(), CommandError> {
+    if len > MAX_STRING_LEN {
+        return Err(CommandError::InvalidArgument(
+            "string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// LCS 中一段连续匹配：两个字符串中的闭区间以及长度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LcsMatch {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+    pub len: usize,
+}
+
+/// 计算最长公共子序列，返回子序列本身以及从后往前的匹配区间（与 Redis 的输出顺序一致）。
+///
+/// DP 表占用 (alen+1)*(blen+1) 个 u32，超过 proto-max-bulk-len 时直接报错。
+pub fn lcs(
+    a: &[u8],
+    b: &[u8],
+    min_match_len: usize,
+) -> Result<(Vec<u8>, Vec<LcsMatch>), CommandError> {
+    let (alen, blen) = (a.len(), b.len());
+    // dp[i][j] 为 a[..i] 与 b[..j] 的 LCS 长度
+    let width = blen + 1;
+    let cells = (alen + 1).checked_mul(width);
+    if cells.and_then(|n| n.checked_mul(size_of::<u32>())).is_none_or(|n| n > MAX_STRING_LEN) {
+        return Err(CommandError::InvalidArgument(
+            "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len".to_string(),
+        ));
+    }
+    let mut dp = vec![0u32; (alen + 1) * width];
+    for i in 1..=alen {
+        for j in 1..=blen {
+            dp[i * width + j] = if a[i - 1] == b[j - 1] {
+                dp[(i - 1) * width + j - 1] + 1
+            } else {
+                dp[(i - 1) * width + j].max(dp[i * width + j - 1])
+            };
+        }
+    }
+
+    let mut result = vec![0u8; dp[alen * width + blen] as usize];
+    let mut idx = result.len();
+    let mut matches = vec![];
+    let (mut i, mut j) = (alen, blen);
+    // 当前正在扩展的匹配区间，None 表示没有
+    let mut range: Option<((usize, usize), (usize, usize))> = None;
+    while i > 0 && j > 0 {
+        let mut emit = false;
+        if a[i - 1] == b[j - 1] {
+            result[idx - 1] = a[i - 1];
+            range = match range {
+                None => Some(((i - 1, i - 1), (j - 1, j - 1))),
+                // 与上一个匹配相邻，向前扩展
+                Some(((astart, aend), (bstart, bend))) if astart == i && bstart == j => {
+                    Some(((astart - 1, aend), (bstart - 1, bend)))
+                }
+                Some(r) => {
+                    emit = true;
+                    Some(r)
+                }
+            };
+            if let Some(((astart, _), (bstart, _))) = range {
+                if astart == 0 || bstart == 0 {
+                    emit = true;
+                }
+            }
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if dp[(i - 1) * width + j] > dp[i * width + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            emit = range.is_some();
+        }
+        if emit {
+            if let Some((a_range, b_range)) = range.take() {
+                let len = a_range.1 - a_range.0 + 1;
+                if len >= min_match_len {
+                    matches.push(LcsMatch { a: a_range, b: b_range, len });
+                }
+            }
+        }
+    }
+    Ok((result, matches))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parse_i64() {
+        assert_eq!(parse_i64(b"123"), Some(123));
+        assert_eq!(parse_i64(b"-123"), Some(-123));
+        assert_eq!(parse_i64(b"0"), Some(0));
+        assert_eq!(parse_i64(b"-0"), None);
+        assert_eq!(parse_i64(b"007"), None);
+        assert_eq!(parse_i64(b"+1"), None);
+        assert_eq!(parse_i64(b" 1"), None);
+        assert_eq!(parse_i64(b"9223372036854775808"), None);
+        assert_eq!(StringValue::new(Bytes::from_static(b"42")), StringValue::Int(42));
+        assert_eq!(StringValue::new(Bytes::from_static(b"042")), StringValue::Raw(Bytes::from_static(b"042")));
+    }
+
+    #[test]
+    fn test_get_shares_stored_bytes() -> Result<(), CommandError> {
+        let backend = Backend::new();
+        let value = Bytes::from(vec![b'x'; 1024]);
+        backend.set(Bytes::from_static(b"blob"), value.clone());
+        // 读取只增加引用计数，与写入的数据是同一块内存
+        assert_eq!(backend.get(b"blob")?.unwrap().as_ptr(), value.as_ptr());
+        assert_eq!(backend.getrange(b"blob", 1, 10)?.as_ptr(), value[1..].as_ptr());
+
+        // 原地修改不会影响之前读出的值
+        let before = backend.get(b"blob")?.unwrap();
+        backend.append(b"blob", b"y")?;
+        assert_eq!(before.len(), 1024);
+        assert_eq!(backend.strlen(b"blob")?, 1025);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_too_long_keeps_keyspace_unchanged() {
+        let backend = Backend::new();
+        // 全零的大块内存按需分配，不会真正占用 512MB
+        let huge = vec![0u8; MAX_STRING_LEN + 1];
+        assert!(backend.append(b"missing", &huge).is_err());
+        assert!(!backend.exists(b"missing"));
+
+        backend.set(Bytes::from_static(b"key"), Bytes::from_static(b"abc"));
+        assert!(backend.append(b"key", &huge).is_err());
+        assert_eq!(backend.get(b"key").unwrap(), Some(Bytes::from_static(b"abc")));
+    }
+
+    #[test]
+    fn test_incr_by_concurrently() {
+        let backend = Backend::new();
+        let handles = (0..8)
+            .map(|_| {
+                let backend = backend.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        backend.incr_by(b"counter", 1).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(backend.get(b"counter").unwrap(), Some(Bytes::from_static(b"8000")));
+    }
+
+    #[test]
+    fn test_incr_by_float() {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"f"), Bytes::from_static(b"10.50"));
+        assert_eq!(backend.incr_by_float(b"f", 0.1).unwrap(), Bytes::from_static(b"10.6"));
+        assert_eq!(backend.incr_by_float(b"f", -5.6).unwrap(), Bytes::from_static(b"5"));
+        backend.set(Bytes::from_static(b"s"), Bytes::from_static(b"abc"));
+        assert!(matches!(backend.incr_by_float(b"s", 1.0), Err(CommandError::NotFloat)));
+    }
+
+    #[test]
+    fn test_incr_f64_formats_like_redis() {
+        assert_eq!(incr_f64(0.1, 0.2).unwrap(), "0.3");
+        assert_eq!(incr_f64(1.1, 2.2).unwrap(), "3.3");
+        assert_eq!(incr_f64(10.5, 0.1).unwrap(), "10.6");
+        assert_eq!(incr_f64(0.3, -0.3).unwrap(), "0");
+        assert_eq!(incr_f64(-1.0, 0.25).unwrap(), "-0.75");
+        assert_eq!(incr_f64(5.0e3, 0.0).unwrap(), "5000");
+        assert_eq!(incr_f64(1e-5, 0.0).unwrap(), "0.00001");
+        // 超过 17 位有效数字时四舍五入
+        assert_eq!(incr_f64(0.1, 1e-20).unwrap(), "0.1");
+        assert_eq!(incr_f64(99999999999999999.0, 0.5).unwrap(), "100000000000000000");
+        // 超出 i128 的范围时使用 f64 的和，也不使用指数形式
+        assert_eq!(incr_f64(1e300, 1e300).unwrap(), format!("2{}", "0".repeat(300)));
+        assert_eq!(incr_f64(f64::MAX, f64::MAX), None);
+    }
+
+    #[test]
+    fn test_getrange_setrange() {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"s"), Bytes::from_static(b"This is a string"));
+        assert_eq!(backend.getrange(b"s", 0, 3).unwrap(), Bytes::from_static(b"This"));
+        assert_eq!(backend.getrange(b"s", -3, -1).unwrap(), Bytes::from_static(b"ing"));
+        assert_eq!(backend.getrange(b"s", 0, -1).unwrap(), Bytes::from_static(b"This is a string"));
+        assert_eq!(backend.getrange(b"s", 10, 100).unwrap(), Bytes::from_static(b"string"));
+        assert_eq!(backend.getrange(b"s", 5, 3).unwrap(), Bytes::from_static(b""));
+
+        assert_eq!(backend.setrange(b"p", 3, b"abc").unwrap(), 6);
+        assert_eq!(backend.get(b"p").unwrap(), Some(Bytes::from_static(b"\0\0\0abc")));
+        assert_eq!(backend.setrange(b"none", 3, b"").unwrap(), 0);
+        assert!(!backend.exists(b"none"));
+    }
+
+    #[test]
+    fn test_lcs() -> Result<(), CommandError> {
+        let (seq, matches) = lcs(b"ohmytext", b"mynewtext", 0)?;
+        assert_eq!(seq, Bytes::from_static(b"mytext"));
+        assert_eq!(
+            matches,
+            vec![
+                LcsMatch { a: (4, 7), b: (5, 8), len: 4 },
+                LcsMatch { a: (2, 3), b: (0, 1), len: 2 },
+            ]
+        );
+        let (_, matches) = lcs(b"ohmytext", b"mynewtext", 4)?;
+        assert_eq!(matches.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_memory_limit() {
+        // 12000 * 12000 个 u32 超过 512MB，在分配 DP 表之前就报错
+        let a = vec![b'a'; 12_000];
+        let err = lcs(&a, &a, 0).unwrap_err();
+        assert_eq!(
+            crate::RespFrame::from(err),
+            crate::SimpleError::new(
+                "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+            )
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_mset_is_atomic_for_mget() {
+        let backend = Backend::new();
+        let keys = [Bytes::from_static(b"a"), Bytes::from_static(b"b"), Bytes::from_static(b"c")];
+        let writer = {
+            let backend = backend.clone();
+            let keys = keys.clone();
+            thread::spawn(move || {
+                for i in 0..2000 {
+                    let value = Bytes::from(i.to_string());
+                    backend.mset(keys.iter().map(|k| (k.clone(), value.clone())).collect());
+                }
+            })
+        };
+        for _ in 0..2000 {
+            let values = backend.mget(&keys);
+            assert!(values.iter().all(|v| *v == values[0]));
+        }
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_msetnx() {
+        let backend = Backend::new();
+        let pairs = |keys: &[&str]| keys.iter().map(|k| (Bytes::copy_from_slice(k.as_bytes()), Bytes::from_static(b"v"))).collect();
+        assert!(backend.msetnx(pairs(&["a", "b"])));
+        assert!(!backend.msetnx(pairs(&["b", "c"])));
+        assert!(!backend.exists(b"c"));
+        // 同一个 key 出现多次时以最后一个值为准
+        let dup = vec![(Bytes::from_static(b"d"), Bytes::from_static(b"1")), (Bytes::from_static(b"d"), Bytes::from_static(b"2"))];
+        assert!(backend.msetnx(dup));
+        assert_eq!(backend.get(b"d").unwrap(), Some(Bytes::from_static(b"2")));
+    }
+}
\ No newline at end of file
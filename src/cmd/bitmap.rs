@@ -0,0 +1,384 @@
+use bytes::Bytes;
+use crate::backend::{
+    bitcount, bitpos, Backend, BitFieldOp, BitFieldOverflow, BitFieldType, BitOpKind, BitUnit, MAX_BIT_OFFSET,
+};
+use crate::cmd::{
+    extract_args, extract_keys, frame_to_key, frame_to_string, parse_integer, validate_command, BitCount,
+    BitField, BitFieldRo, BitOp, BitPos, CommandError, CommandExecutor, GetBit, SetBit,
+};
+use crate::{RespArray, RespFrame, RespNull};
+
+impl CommandExecutor for SetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.setbit(&self.key, self.offset, self.bit) {
+            Ok(old) => RespFrame::Integer(old as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for GetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getbit(&self.key, self.offset) {
+            Ok(bit) => RespFrame::Integer(bit as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for BitCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.get(&self.key) {
+            Ok(value) => {
+                let count = bitcount(&value.unwrap_or_default(), self.range);
+                RespFrame::Integer(count as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for BitPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.get(&self.key) {
+            Ok(value) => RespFrame::Integer(bitpos(
+                &value.unwrap_or_default(),
+                self.bit,
+                self.start,
+                self.end,
+                self.unit,
+            )),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for BitOp {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bitop(self.op, self.dest, &self.keys) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for BitField {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        bitfield_reply(backend, &self.key, &self.ops)
+    }
+}
+impl CommandExecutor for BitFieldRo {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        bitfield_reply(backend, &self.key, &self.ops)
+    }
+}
+
+// FAIL 溢出的子命令返回 nil
+fn bitfield_reply(backend: &Backend, key: &[u8], ops: &[BitFieldOp]) -> RespFrame {
+    match backend.bitfield(key, ops) {
+        Ok(results) => RespArray::new(
+            results
+                .into_iter()
+                .map(|n| match n {
+                    Some(n) => RespFrame::Integer(n),
+                    None => RespFrame::Null(RespNull),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+fn invalid_offset() -> CommandError {
+    CommandError::InvalidArgument("bit offset is not an integer or out of range".to_string())
+}
+
+fn parse_offset(frame: &RespFrame) -> Result<u64, CommandError> {
+    match parse_integer(frame) {
+        Ok(n) if (0..=MAX_BIT_OFFSET as i64).contains(&n) => Ok(n as u64),
+        _ => Err(invalid_offset()),
+    }
+}
+
+fn parse_unit(frame: RespFrame) -> Result<BitUnit, CommandError> {
+    match frame_to_string(frame)?.to_ascii_lowercase().as_str() {
+        "byte" => Ok(BitUnit::Byte),
+        "bit" => Ok(BitUnit::Bit),
+        _ => Err(CommandError::SyntaxError),
+    }
+}
+
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setbit"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(offset), Some(bit)) => {
+                let bit = match parse_integer(&bit) {
+                    Ok(bit @ (0 | 1)) => bit as u8,
+                    _ => {
+                        return Err(CommandError::InvalidArgument(
+                            "bit is not an integer or out of range".to_string(),
+                        ))
+                    }
+                };
+                Ok(SetBit {
+                    key: frame_to_key(key)?,
+                    offset: parse_offset(&offset)?,
+                    bit,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid key, offset or bit".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getbit"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(offset)) => Ok(GetBit {
+                key: frame_to_key(key)?,
+                offset: parse_offset(&offset)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or offset".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for BitCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitcount"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?;
+        let range = match (args.next(), args.next(), args.next(), args.next()) {
+            (None, ..) => None,
+            (Some(start), Some(end), unit, None) => {
+                let unit = unit.map(parse_unit).transpose()?.unwrap_or_default();
+                Some((parse_integer(&start)?, parse_integer(&end)?, unit))
+            }
+            _ => return Err(CommandError::SyntaxError),
+        };
+        Ok(BitCount { key, range })
+    }
+}
+impl TryFrom<RespArray> for BitPos {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitpos"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?;
+        let bit = match parse_integer(&args.next().ok_or(CommandError::SyntaxError)?) {
+            Ok(bit @ (0 | 1)) => bit as u8,
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "The bit argument must be 1 or 0.".to_string(),
+                ))
+            }
+        };
+        let start = args.next().map(|s| parse_integer(&s)).transpose()?.unwrap_or(0);
+        let end = args.next().map(|e| parse_integer(&e)).transpose()?;
+        let unit = args.next().map(parse_unit).transpose()?.unwrap_or_default();
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        Ok(BitPos { key, bit, start, end, unit })
+    }
+}
+impl TryFrom<RespArray> for BitOp {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitop"])?;
+        let mut args = extract_keys(value, 1)?.into_iter();
+        let op = args.next().unwrap_or_default().to_ascii_lowercase();
+        let op = match op.as_slice() {
+            b"and" => BitOpKind::And,
+            b"or" => BitOpKind::Or,
+            b"xor" => BitOpKind::Xor,
+            b"not" => BitOpKind::Not,
+            _ => return Err(CommandError::SyntaxError),
+        };
+        let dest = args.next().ok_or(CommandError::SyntaxError)?;
+        let keys: Vec<Bytes> = args.collect();
+        if op == BitOpKind::Not && keys.len() != 1 {
+            return Err(CommandError::InvalidArgument(
+                "BITOP NOT must be called with a single source key.".to_string(),
+            ));
+        }
+        Ok(BitOp { op, dest, keys })
+    }
+}
+impl TryFrom<RespArray> for BitField {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitfield"])?;
+        let (key, ops) = parse_bitfield_args(value)?;
+        Ok(BitField { key, ops })
+    }
+}
+impl TryFrom<RespArray> for BitFieldRo {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bitfield_ro"])?;
+        let (key, ops) = parse_bitfield_args(value)?;
+        if !ops.iter().all(|op| matches!(op, BitFieldOp::Get(..))) {
+            return Err(CommandError::InvalidArgument(
+                "BITFIELD_RO only supports the GET subcommand".to_string(),
+            ));
+        }
+        Ok(BitFieldRo { key, ops })
+    }
+}
+
+// key [GET encoding offset] [SET encoding offset value] [INCRBY encoding offset increment]
+//     [OVERFLOW WRAP | SAT | FAIL] ...
+fn parse_bitfield_args(value: RespArray) -> Result<(Bytes, Vec<BitFieldOp>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?;
+    let mut ops = Vec::new();
+    while let Some(arg) = args.next() {
+        let sub = frame_to_string(arg)?.to_ascii_lowercase();
+        if sub == "overflow" {
+            let overflow = frame_to_string(args.next().ok_or(CommandError::SyntaxError)?)?;
+            let overflow = match overflow.to_ascii_lowercase().as_str() {
+                "wrap" => BitFieldOverflow::Wrap,
+                "sat" => BitFieldOverflow::Sat,
+                "fail" => BitFieldOverflow::Fail,
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "Invalid OVERFLOW type specified".to_string(),
+                    ))
+                }
+            };
+            ops.push(BitFieldOp::Overflow(overflow));
+            continue;
+        }
+        if !matches!(sub.as_str(), "get" | "set" | "incrby") {
+            return Err(CommandError::SyntaxError);
+        }
+        let (ty, offset) = match (args.next(), args.next()) {
+            (Some(ty), Some(offset)) => parse_bitfield_field(ty, offset)?,
+            _ => return Err(CommandError::SyntaxError),
+        };
+        let op = match sub.as_str() {
+            "get" => BitFieldOp::Get(ty, offset),
+            _ => {
+                let n = parse_integer(&args.next().ok_or(CommandError::SyntaxError)?)?;
+                if sub == "set" {
+                    BitFieldOp::Set(ty, offset, n)
+                } else {
+                    BitFieldOp::IncrBy(ty, offset, n)
+                }
+            }
+        };
+        ops.push(op);
+    }
+    Ok((key, ops))
+}
+
+// 偏移量以 # 开头时按类型宽度倍数计算，如 u8 #2 表示第 16 位
+fn parse_bitfield_field(ty: RespFrame, offset: RespFrame) -> Result<(BitFieldType, u64), CommandError> {
+    let ty = BitFieldType::parse(&frame_to_string(ty)?).ok_or_else(|| {
+        CommandError::InvalidArgument(
+            "Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+                .to_string(),
+        )
+    })?;
+    let offset = frame_to_string(offset).map_err(|_| invalid_offset())?;
+    let offset = match offset.strip_prefix('#') {
+        Some(n) => n.parse::<u64>().ok().and_then(|n| n.checked_mul(ty.bits as u64)),
+        None => offset.parse::<u64>().ok(),
+    };
+    match offset {
+        Some(offset) if offset + ty.bits as u64 - 1 <= MAX_BIT_OFFSET => Ok((ty, offset)),
+        _ => Err(invalid_offset()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, SimpleError};
+    use anyhow::Result;
+
+    fn args(args: &[&str]) -> RespArray {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    #[test]
+    fn test_setbit_getbit_bitcount_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd: SetBit = args(&["setbit", "bits", "7", "1"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd: SetBit = args(&["setbit", "bits", "7", "0"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd: GetBit = args(&["getbit", "bits", "100"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let err = SetBit::try_from(args(&["setbit", "bits", "7", "2"])).unwrap_err();
+        assert_eq!(err.to_string(), "bit is not an integer or out of range");
+        let err = SetBit::try_from(args(&["setbit", "bits", "4294967296", "1"])).unwrap_err();
+        assert_eq!(err.to_string(), "bit offset is not an integer or out of range");
+
+        backend.set(Bytes::from_static(b"foo"), Bytes::from_static(b"foobar"));
+        let cmd: BitCount = args(&["bitcount", "foo"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(26));
+        let cmd: BitCount = args(&["bitcount", "foo", "5", "30", "BIT"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(17));
+        assert!(BitCount::try_from(args(&["bitcount", "foo", "1"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitpos_bitop_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"a"), Bytes::from_static(&[0xff, 0xf0, 0x00]));
+        let cmd: BitPos = args(&["bitpos", "a", "0"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(12));
+        let cmd: BitPos = args(&["bitpos", "missing", "1"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        let cmd: BitOp = args(&["bitop", "NOT", "dest", "a"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(backend.get(b"dest")?, Some(Bytes::from_static(&[0x00, 0x0f, 0xff])));
+        let cmd: BitOp = args(&["bitop", "and", "dest", "missing"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(!backend.exists(b"dest"));
+        assert!(BitOp::try_from(args(&["bitop", "not", "dest", "a", "b"])).is_err());
+
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"f"), Bytes::from_static(b"v"))])?;
+        let cmd: BitOp = args(&["bitop", "or", "dest", "a", "map"]).try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitfield_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd: BitField = args(&[
+            "bitfield", "bf", "OVERFLOW", "FAIL", "INCRBY", "u2", "#1", "4", "SET", "u8", "#1", "255",
+            "GET", "u4", "8",
+        ])
+        .try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([RespFrame::Null(RespNull), 0.into(), 15.into()]).into()
+        );
+
+        let cmd: BitFieldRo = args(&["bitfield_ro", "bf", "GET", "i8", "8"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespArray::new([(-1).into()]).into());
+        assert!(BitFieldRo::try_from(args(&["bitfield_ro", "bf", "SET", "i8", "0", "1"])).is_err());
+        assert!(BitField::try_from(args(&["bitfield", "bf", "GET", "u64", "0"])).is_err());
+
+        // 只读的 BITFIELD 不创建 key
+        let cmd: BitField = args(&["bitfield", "none", "GET", "u8", "0"]).try_into()?;
+        assert_eq!(cmd.execute(&backend), RespArray::new([0.into()]).into());
+        assert!(!backend.exists(b"none"));
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+use crate::backend::Backend;
+use crate::cmd::table::{self, CommandSpec, COMMAND_TABLE};
+use crate::cmd::{
+    extract_args, validate_command, CommandCount, CommandDocs, CommandError, CommandExecutor,
+    CommandGetKeys, CommandHelp, CommandInfo, CommandList,
+};
+use crate::{BulkString, RespArray, RespFrame, RespMap, RespNull, SimpleString};
+
+impl CommandExecutor for CommandList {
+    fn execute(self, _: &Backend) -> RespFrame {
+        all_command_infos()
+    }
+}
+
+impl CommandExecutor for CommandCount {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::Integer(top_level_commands().count() as i64)
+    }
+}
+
+// 与 Redis 的 COMMAND HELP 一致，只列出已实现的子命令
+const COMMAND_HELP: &[&str] = &[
+    "COMMAND <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "(no subcommand)",
+    "    Return details about all Redis commands.",
+    "COUNT",
+    "    Return the total number of commands in this Redis server.",
+    "INFO [<command-name> ...]",
+    "    Return details about multiple Redis commands.",
+    "    If no command names are given, documentation details for all",
+    "    commands are returned.",
+    "DOCS [<command-name> ...]",
+    "    Return documentation details about multiple Redis commands.",
+    "    If no command names are given, documentation details for all",
+    "    commands are returned.",
+    "GETKEYS <full-command>",
+    "    Return the keys from a full Redis command.",
+    "HELP",
+    "    Print this help.",
+];
+
+impl CommandExecutor for CommandHelp {
+    fn execute(self, _: &Backend) -> RespFrame {
+        let lines = COMMAND_HELP.iter().map(|line| SimpleString::new(*line).into());
+        RespArray::new(lines.collect::<Vec<RespFrame>>()).into()
+    }
+}
+
+impl CommandExecutor for CommandInfo {
+    fn execute(self, _: &Backend) -> RespFrame {
+        if self.names.is_empty() {
+            return all_command_infos();
+        }
+        let infos = self
+            .names
+            .iter()
+            .map(|name| match table::lookup(name) {
+                Some(spec) => command_info(spec),
+                None => RespFrame::Null(RespNull),
+            })
+            .collect::<Vec<_>>();
+        RespArray::new(infos).into()
+    }
+}
+
+impl CommandExecutor for CommandDocs {
+    fn execute(self, _: &Backend) -> RespFrame {
+        let specs: Vec<&CommandSpec> = if self.names.is_empty() {
+            top_level_commands().collect()
+        } else {
+            self.names.iter().filter_map(|name| table::lookup(name)).collect()
+        };
+        // 未知命令在 DOCS 中直接忽略，与 Redis 一致
+        specs
+            .into_iter()
+            .map(|spec| (BulkString::from(spec.name), command_doc(spec)))
+            .collect::<RespMap>()
+            .into()
+    }
+}
+
+impl CommandExecutor for CommandGetKeys {
+    fn execute(self, _: &Backend) -> RespFrame {
+        let name = match self.args.first() {
+            Some(RespFrame::BulkString(name)) => String::from_utf8_lossy(name).to_ascii_lowercase(),
+            _ => return invalid("Invalid command specified"),
+        };
+        let spec = match table::lookup(&name) {
+            Some(spec) => spec,
+            None => return invalid("Invalid command specified"),
+        };
+        if !spec.check_arity(self.args.len()) {
+            return invalid("Invalid number of arguments specified for command");
+        }
+        let indexes = spec.key_indexes(self.args.len());
+        if indexes.is_empty() {
+            return invalid("The command has no key arguments");
+        }
+        let keys = indexes
+            .into_iter()
+            .filter_map(|i| self.args.get(i).cloned())
+            .collect::<Vec<_>>();
+        RespArray::new(keys).into()
+    }
+}
+
+impl TryFrom<RespArray> for CommandList {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command"])?;
+        Ok(CommandList)
+    }
+}
+
+impl TryFrom<RespArray> for CommandCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "count"])?;
+        Ok(CommandCount)
+    }
+}
+
+impl TryFrom<RespArray> for CommandHelp {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "help"])?;
+        Ok(CommandHelp)
+    }
+}
+
+impl TryFrom<RespArray> for CommandInfo {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "info"])?;
+        Ok(CommandInfo {
+            names: command_names(extract_args(value, 2)?),
+        })
+    }
+}
+
+impl TryFrom<RespArray> for CommandDocs {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "docs"])?;
+        Ok(CommandDocs {
+            names: command_names(extract_args(value, 2)?),
+        })
+    }
+}
+
+impl TryFrom<RespArray> for CommandGetKeys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["command", "getkeys"])?;
+        Ok(CommandGetKeys {
+            args: extract_args(value, 2)?,
+        })
+    }
+}
+
+fn top_level_commands() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMAND_TABLE.iter().filter(|spec| !spec.is_subcommand())
+}
+
+fn all_command_infos() -> RespFrame {
+    let infos = top_level_commands().map(command_info).collect::<Vec<_>>();
+    RespArray::new(infos).into()
+}
+
+fn command_names(args: Vec<RespFrame>) -> Vec<String> {
+    args.into_iter()
+        .filter_map(|arg| match arg {
+            RespFrame::BulkString(name) => Some(String::from_utf8_lossy(&name).to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn invalid(msg: &str) -> RespFrame {
+    CommandError::InvalidArgument(msg.to_string()).into()
+}
+
+// [name, arity, flags, first_key, last_key, step, acl_categories, tips, key_specs, subcommands]
+fn command_info(spec: &CommandSpec) -> RespFrame {
+    let flags = spec
+        .flags
+        .iter()
+        .map(|flag| SimpleString::new(flag.as_str()).into())
+        .collect::<Vec<RespFrame>>();
+    let subcommands = spec.subcommands().map(command_info).collect::<Vec<_>>();
+    RespArray::new([
+        BulkString::from(spec.name).into(),
+        spec.arity.into(),
+        RespArray::new(flags).into(),
+        spec.first_key.into(),
+        spec.last_key.into(),
+        spec.step.into(),
+        RespArray::new([SimpleString::new(format!("@{}", spec.group)).into()]).into(),
+        RespArray::new([]).into(),
+        RespArray::new([]).into(),
+        RespArray::new(subcommands).into(),
+    ])
+    .into()
+}
+
+fn command_doc(spec: &CommandSpec) -> RespFrame {
+    let mut doc = RespMap::new();
+    doc.insert(BulkString::from("summary"), BulkString::from(spec.summary).into());
+    doc.insert(BulkString::from("group"), BulkString::from(spec.group).into());
+    let subcommands = spec
+        .subcommands()
+        .map(|sub| (BulkString::from(sub.name), command_doc(sub)))
+        .collect::<RespMap>();
+    if !subcommands.is_empty() {
+        doc.insert(BulkString::from("subcommands"), subcommands.into());
+    }
+    doc.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Command;
+    use crate::{RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn parse(input: &[u8]) -> Result<Command> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(input);
+        let frame = RespArray::decode(&mut buf)?;
+        Ok(frame.try_into()?)
+    }
+
+    #[test]
+    fn test_command_count() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = parse(b"*2\r\n$7\r\nCOMMAND\r\n$5\r\nCOUNT\r\n")?;
+        let expected = COMMAND_TABLE.iter().filter(|s| !s.is_subcommand()).count();
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(expected as i64));
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_info() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = parse(b"*4\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nget\r\n$3\r\nfoo\r\n")?;
+        let ret = cmd.execute(&backend);
+        let RespFrame::Array(infos) = ret else {
+            panic!("expected array, got {:?}", ret);
+        };
+        assert_eq!(infos.len(), 2);
+        let RespFrame::Array(ref get) = infos[0] else {
+            panic!("expected array, got {:?}", infos[0]);
+        };
+        assert_eq!(get[0], BulkString::from("get").into());
+        assert_eq!(get[1], RespFrame::Integer(2));
+        assert_eq!(infos[1], RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_getkeys() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = parse(b"*5\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\nhget\r\n$3\r\nmap\r\n$5\r\nfield\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::from("map").into()]).into()
+        );
+
+        let cmd = parse(b"*4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\nhget\r\n$3\r\nmap\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR Invalid number of arguments specified for command").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_help() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = parse(b"*2\r\n$7\r\ncommand\r\n$4\r\nHELP\r\n")?;
+        let RespFrame::Array(lines) = cmd.execute(&backend) else {
+            panic!("expected array");
+        };
+        assert_eq!(lines.len(), COMMAND_HELP.len());
+        assert_eq!(lines[lines.len() - 1], SimpleString::new("    Print this help.").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_unknown_subcommand() {
+        let ret = parse(b"*2\r\n$7\r\ncommand\r\n$3\r\nfoo\r\n");
+        let err = ret.unwrap_err().downcast::<CommandError>().unwrap();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR unknown subcommand 'foo'. Try COMMAND HELP.").into()
+        );
+    }
+}
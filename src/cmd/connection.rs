@@ -0,0 +1,158 @@
+use crate::backend::Backend;
+use crate::cmd::{
+    extract_args, frame_to_string, parse_integer, validate_command, CommandError, CommandExecutor,
+    Hello, RespProtocol,
+};
+use crate::{BulkString, RespArray, RespFrame, RespMap};
+
+// 对外报告的兼容版本，客户端据此判断可用的命令
+const REDIS_VERSION: &str = "7.4.0";
+
+impl Hello {
+    /// 由连接在执行前调用：填入连接 id，未指定版本时沿用当前协议。
+    /// 返回命令执行成功后连接应使用的协议
+    pub fn negotiate(&mut self, client_id: u64, current: RespProtocol) -> RespProtocol {
+        self.client_id = client_id;
+        *self.protocol.get_or_insert(current)
+    }
+}
+
+impl CommandExecutor for Hello {
+    fn execute(self, _: &Backend) -> RespFrame {
+        // 没有配置 ACL，default 用户不需要密码
+        if let Some((user, _)) = &self.auth {
+            if user != "default" {
+                return CommandError::WrongPass.into();
+            }
+        }
+        let proto = match self.protocol.unwrap_or_default() {
+            RespProtocol::Resp2 => 2,
+            RespProtocol::Resp3 => 3,
+        };
+        RespMap::from_iter([
+            (BulkString::from("server"), BulkString::from("redis").into()),
+            (BulkString::from("version"), BulkString::from(REDIS_VERSION).into()),
+            (BulkString::from("proto"), RespFrame::Integer(proto)),
+            (BulkString::from("id"), RespFrame::Integer(self.client_id as i64)),
+            (BulkString::from("mode"), BulkString::from("standalone").into()),
+            (BulkString::from("role"), BulkString::from("master").into()),
+            (BulkString::from("modules"), RespArray::new([]).into()),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hello"])?;
+        let mut hello = Hello { protocol: None, auth: None, client_id: 0 };
+        let mut args = extract_args(value, 1)?.into_iter();
+        let Some(protover) = args.next() else {
+            return Ok(hello);
+        };
+        hello.protocol = match parse_integer(&protover) {
+            Ok(2) => Some(RespProtocol::Resp2),
+            Ok(3) => Some(RespProtocol::Resp3),
+            Ok(_) => return Err(CommandError::NoProto),
+            Err(_) => {
+                return Err(CommandError::InvalidArgument(
+                    "Protocol version is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+        while let Some(arg) = args.next() {
+            let option = frame_to_string(arg)?;
+            match (option.to_ascii_lowercase().as_str(), args.len()) {
+                ("auth", 2..) => {
+                    let user = frame_to_string(args.next().unwrap())?;
+                    let pass = frame_to_string(args.next().unwrap())?;
+                    hello.auth = Some((user, pass));
+                }
+                ("setname", 1..) => {
+                    let name = frame_to_string(args.next().unwrap())?;
+                    // 与 CLIENT SETNAME 相同，名字中不能有空格和控制字符；
+                    // 目前还没有 CLIENT 命令，名字只做校验
+                    if name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) {
+                        return Err(CommandError::InvalidArgument(
+                            "Client names cannot contain spaces, newlines or special characters."
+                                .to_string(),
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(CommandError::InvalidArgument(format!(
+                        "Syntax error in HELLO option '{}'",
+                        option
+                    )))
+                }
+            }
+        }
+        Ok(hello)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Command;
+    use crate::{RespDecode, RespEncode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn parse(input: &[u8]) -> Result<Command, CommandError> {
+        let mut buf = BytesMut::from(input);
+        RespArray::decode(&mut buf)?.try_into()
+    }
+
+    #[test]
+    fn test_hello_negotiate() -> Result<()> {
+        let backend = Backend::new();
+        let Command::Hello(mut hello) = parse(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n")? else {
+            panic!("expected HELLO");
+        };
+        assert_eq!(hello.negotiate(7, RespProtocol::Resp2), RespProtocol::Resp3);
+        let RespFrame::Map(info) = hello.execute(&backend) else {
+            panic!("expected map");
+        };
+        assert_eq!(info.get(&b"proto".into()), Some(&RespFrame::Integer(3)));
+        assert_eq!(info.get(&b"id".into()), Some(&RespFrame::Integer(7)));
+
+        // 不带版本时保持当前协议
+        let Command::Hello(mut hello) = parse(b"*1\r\n$5\r\nHELLO\r\n")? else {
+            panic!("expected HELLO");
+        };
+        assert_eq!(hello.negotiate(7, RespProtocol::Resp3), RespProtocol::Resp3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_errors() -> Result<()> {
+        let backend = Backend::new();
+        let err = parse(b"*2\r\n$5\r\nhello\r\n$1\r\n4\r\n").unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+
+        let ret = parse(b"*3\r\n$5\r\nhello\r\n$1\r\n3\r\n$4\r\nauth\r\n");
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+
+        let cmd = parse(b"*5\r\n$5\r\nhello\r\n$1\r\n3\r\n$4\r\nAUTH\r\n$5\r\nalice\r\n$1\r\np\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("WRONGPASS invalid username-password pair or user is disabled.").into()
+        );
+
+        let ret = parse(b"*4\r\n$5\r\nhello\r\n$1\r\n2\r\n$7\r\nsetname\r\n$3\r\na b\r\n");
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+
+        // 未知选项原样回显，其中的换行不能拆出第二个回复
+        let err = parse(b"*3\r\n$5\r\nhello\r\n$1\r\n3\r\n$6\r\nx\r\n+OK\r\n").unwrap_err();
+        assert_eq!(
+            RespFrame::from(err).encode(),
+            b"-ERR Syntax error in HELLO option 'x  +OK'\r\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use bytes::Bytes;
+use crate::backend::{now_ms, Backend, ExpireCondition};
+use crate::cmd::{
+    extract_args, frame_to_key, frame_to_string, parse_integer, validate_command, CommandError,
+    CommandExecutor, Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Persist,
+    Ttl,
+};
+use crate::{RespArray, RespFrame};
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(now_ms()));
+        expire_generic(backend, &self.key, at, self.condition, "expire")
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(now_ms());
+        expire_generic(backend, &self.key, at, self.condition, "pexpire")
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.checked_mul(1000);
+        expire_generic(backend, &self.key, at, self.condition, "expireat")
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_generic(backend, &self.key, Some(self.timestamp), self.condition, "pexpireat")
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.pexpiretime(&self.key) {
+            at if at < 0 => RespFrame::Integer(at),
+            // 四舍五入到秒，与 Redis 一致
+            at => RespFrame::Integer(((at - now_ms()).max(0) + 500) / 1000),
+        }
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.pexpiretime(&self.key) {
+            at if at < 0 => RespFrame::Integer(at),
+            at => RespFrame::Integer((at - now_ms()).max(0)),
+        }
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.pexpiretime(&self.key) {
+            at if at < 0 => RespFrame::Integer(at),
+            at => RespFrame::Integer(at / 1000),
+        }
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.pexpiretime(&self.key))
+    }
+}
+
+// at 为 None 表示计算过期时间时发生了溢出
+fn expire_generic(
+    backend: &Backend,
+    key: &[u8],
+    at: Option<i64>,
+    condition: ExpireCondition,
+    name: &str,
+) -> RespFrame {
+    match at {
+        Some(at) => RespFrame::Integer(backend.expire_at(key, at, condition) as i64),
+        None => CommandError::InvalidArgument(format!(
+            "invalid expire time in '{}' command",
+            name
+        ))
+        .into(),
+    }
+}
+
+// key time [NX | XX | GT | LT]
+fn parse_expire_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(Bytes, i64, ExpireCondition), CommandError> {
+    validate_command(&value, &[name])?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let (key, time) = match (args.next(), args.next()) {
+        (Some(key), Some(time)) => (frame_to_key(key)?, parse_integer(&time)?),
+        _ => return Err(CommandError::InvalidNumberOfArguments(name.to_string())),
+    };
+    let mut condition = ExpireCondition::default();
+    for arg in args {
+        let arg = frame_to_string(arg)?;
+        if !parse_condition_flag(&mut condition, arg.as_bytes()) {
+            return Err(CommandError::InvalidArgument(format!(
+                "Unsupported option {}",
+                arg.to_ascii_lowercase()
+            )));
+        }
+    }
+    check_condition(&condition)?;
+    Ok((key, time, condition))
+}
+
+// 解析一个 NX / XX / GT / LT 选项，不是这些选项时返回 false
+pub(super) fn parse_condition_flag(condition: &mut ExpireCondition, arg: &[u8]) -> bool {
+    match arg.to_ascii_lowercase().as_slice() {
+        b"nx" => condition.nx = true,
+        b"xx" => condition.xx = true,
+        b"gt" => condition.gt = true,
+        b"lt" => condition.lt = true,
+        _ => return false,
+    }
+    true
+}
+
+pub(super) fn check_condition(condition: &ExpireCondition) -> Result<(), CommandError> {
+    if condition.nx && (condition.xx || condition.gt || condition.lt) {
+        return Err(CommandError::InvalidArgument(
+            "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    if condition.gt && condition.lt {
+        return Err(CommandError::InvalidArgument(
+            "GT and LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_key(value: RespArray, name: &'static str) -> Result<Bytes, CommandError> {
+    validate_command(&value, &[name])?;
+    match extract_args(value, 1)?.into_iter().next() {
+        Some(key) => frame_to_key(key),
+        None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition) = parse_expire_args(value, "expire")?;
+        Ok(Expire { key, seconds, condition })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition) = parse_expire_args(value, "pexpire")?;
+        Ok(PExpire { key, milliseconds, condition })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value, "expireat")?;
+        Ok(ExpireAt { key, timestamp, condition })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value, "pexpireat")?;
+        Ok(PExpireAt { key, timestamp, condition })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Ttl { key: parse_key(value, "ttl")? })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PTtl { key: parse_key(value, "pttl")? })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Persist { key: parse_key(value, "persist")? })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(ExpireTime { key: parse_key(value, "expiretime")? })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PExpireTime { key: parse_key(value, "pexpiretime")? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Get;
+    use crate::{RespDecode, RespEncode, RespNull, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_expire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\nXX\r\n$2\r\nLT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Expire = frame.try_into()?;
+        assert_eq!(result.key, "key");
+        assert_eq!(result.seconds, 10);
+        assert!(result.condition.xx && result.condition.lt);
+
+        buf.extend_from_slice(b"*5\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\nNX\r\n$2\r\nGT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Expire::try_from(frame).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR NX and XX, GT or LT options at the same time are not compatible")
+                .into()
+        );
+
+        // 选项原样出现在错误信息中，换行不能拆出第二个回复
+        buf.extend_from_slice(b"*4\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$6\r\nx\r\n+OK\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Expire::try_from(frame).unwrap_err();
+        assert_eq!(RespFrame::from(err).encode(), b"-ERR Unsupported option x  +ok\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_ttl_persist_commands() {
+        let backend = Backend::new();
+        let cmd = Ttl { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+
+        backend.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+        let cmd = Ttl { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        let cmd = Expire {
+            key: Bytes::from_static(b"key"),
+            seconds: 100,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Ttl { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(100));
+
+        let cmd = PExpire {
+            key: Bytes::from_static(b"key"),
+            milliseconds: 200_000,
+            condition: ExpireCondition { lt: true, ..Default::default() },
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = Persist { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = PExpireTime { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        // 过去的时间点会直接删除 key
+        let cmd = ExpireAt {
+            key: Bytes::from_static(b"key"),
+            timestamp: 1,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Get { key: Bytes::from_static(b"key") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_expire_overflow() {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+        let cmd = Expire {
+            key: Bytes::from_static(b"key"),
+            seconds: i64::MAX,
+            condition: ExpireCondition::default(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'expire' command").into()
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use bytes::Bytes;
+use crate::backend::{now_ms, Backend, ExpireCondition};
+use crate::cmd::expire::{check_condition, parse_condition_flag};
+use crate::cmd::{
+    extract_args, frame_to_key, parse_integer, validate_command, CommandError,
+    CommandExecutor, HExpire, HExpireAt, HExpireTime, HPExpire, HPExpireAt, HPExpireTime, HPTtl,
+    HPersist, HTtl,
+};
+use crate::{RespArray, RespFrame};
+
+// 字段过期时间的上限（2^48 毫秒），与 Redis 一致
+const MAX_FIELD_EXPIRE_MS: i64 = (1 << 48) - 1;
+
+impl CommandExecutor for HExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(now_ms()));
+        hexpire_generic(backend, &self.key, &self.fields, self.seconds, at, self.condition, "hexpire")
+    }
+}
+
+impl CommandExecutor for HPExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(now_ms());
+        hexpire_generic(backend, &self.key, &self.fields, self.milliseconds, at, self.condition, "hpexpire")
+    }
+}
+
+impl CommandExecutor for HExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.checked_mul(1000);
+        hexpire_generic(backend, &self.key, &self.fields, self.timestamp, at, self.condition, "hexpireat")
+    }
+}
+
+impl CommandExecutor for HPExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = Some(self.timestamp);
+        hexpire_generic(backend, &self.key, &self.fields, self.timestamp, at, self.condition, "hpexpireat")
+    }
+}
+
+impl CommandExecutor for HTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 四舍五入到秒，与 TTL 一致
+        field_times(backend, &self.key, &self.fields, |at| ((at - now_ms()).max(0) + 500) / 1000)
+    }
+}
+
+impl CommandExecutor for HPTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        field_times(backend, &self.key, &self.fields, |at| (at - now_ms()).max(0))
+    }
+}
+
+impl CommandExecutor for HExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        field_times(backend, &self.key, &self.fields, |at| at / 1000)
+    }
+}
+
+impl CommandExecutor for HPExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        field_times(backend, &self.key, &self.fields, |at| at)
+    }
+}
+
+impl CommandExecutor for HPersist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hpersist(&self.key, &self.fields) {
+            Ok(codes) => integer_array(codes),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// time 是命令中给出的原始参数，at 为 None 表示换算时发生了溢出
+fn hexpire_generic(
+    backend: &Backend,
+    key: &[u8],
+    fields: &[Bytes],
+    time: i64,
+    at: Option<i64>,
+    condition: ExpireCondition,
+    name: &str,
+) -> RespFrame {
+    let at = match at {
+        Some(at) if time >= 0 && at <= MAX_FIELD_EXPIRE_MS => at,
+        _ => {
+            return CommandError::InvalidArgument(format!(
+                "invalid expire time in '{}' command",
+                name
+            ))
+            .into()
+        }
+    };
+    match backend.hexpire_at(key, fields, at, condition) {
+        Ok(codes) => integer_array(codes),
+        Err(e) => e.into(),
+    }
+}
+
+// 负数（-2 / -1）原样返回，其余按 f 换算
+fn field_times(backend: &Backend, key: &[u8], fields: &[Bytes], f: impl Fn(i64) -> i64) -> RespFrame {
+    match backend.hpexpiretime(key, fields) {
+        Ok(times) => integer_array(times.into_iter().map(|at| if at < 0 { at } else { f(at) }).collect()),
+        Err(e) => e.into(),
+    }
+}
+
+fn integer_array(values: Vec<i64>) -> RespFrame {
+    RespArray::new(values.into_iter().map(RespFrame::Integer).collect::<Vec<_>>()).into()
+}
+
+// key time [NX | XX | GT | LT] FIELDS numfields field [field ...]
+fn parse_hexpire_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(Bytes, i64, ExpireCondition, Vec<Bytes>), CommandError> {
+    validate_command(&value, &[name])?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let (key, time) = match (args.next(), args.next()) {
+        (Some(key), Some(time)) => (frame_to_key(key)?, parse_integer(&time)?),
+        _ => return Err(CommandError::InvalidNumberOfArguments(name.to_string())),
+    };
+    let args = args.map(frame_to_key).collect::<Result<Vec<_>, _>>()?;
+    let mut condition = ExpireCondition::default();
+    let flags = args
+        .iter()
+        .take_while(|arg| parse_condition_flag(&mut condition, arg))
+        .count();
+    check_condition(&condition)?;
+    let fields = parse_fields(&args[flags..])?;
+    Ok((key, time, condition, fields))
+}
+
+// key FIELDS numfields field [field ...]
+fn parse_key_fields(value: RespArray, name: &'static str) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    validate_command(&value, &[name])?;
+    let args = extract_args(value, 1)?
+        .into_iter()
+        .map(frame_to_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    let key = args.first().cloned().ok_or(CommandError::SyntaxError)?;
+    Ok((key, parse_fields(&args[1..])?))
+}
+
+// FIELDS numfields field [field ...]
+fn parse_fields(args: &[Bytes]) -> Result<Vec<Bytes>, CommandError> {
+    match args {
+        [keyword, numfields, fields @ ..] if keyword.eq_ignore_ascii_case(b"fields") => {
+            match std::str::from_utf8(numfields).ok().and_then(|n| n.parse::<i64>().ok()) {
+                Some(n) if n > 0 && n as usize == fields.len() => Ok(fields.to_vec()),
+                Some(n) if n > 0 => Err(CommandError::InvalidArgument(
+                    "The `numfields` parameter must match the number of arguments".to_string(),
+                )),
+                _ => Err(CommandError::InvalidArgument(
+                    "Parameter `numFields` should be greater than 0".to_string(),
+                )),
+            }
+        }
+        _ => Err(CommandError::InvalidArgument(
+            "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+        )),
+    }
+}
+
+impl TryFrom<RespArray> for HExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition, fields) = parse_hexpire_args(value, "hexpire")?;
+        Ok(HExpire { key, seconds, condition, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition, fields) = parse_hexpire_args(value, "hpexpire")?;
+        Ok(HPExpire { key, milliseconds, condition, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition, fields) = parse_hexpire_args(value, "hexpireat")?;
+        Ok(HExpireAt { key, timestamp, condition, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition, fields) = parse_hexpire_args(value, "hpexpireat")?;
+        Ok(HPExpireAt { key, timestamp, condition, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "httl")?;
+        Ok(HTtl { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hpttl")?;
+        Ok(HPTtl { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPersist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hpersist")?;
+        Ok(HPersist { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hexpiretime")?;
+        Ok(HExpireTime { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hpexpiretime")?;
+        Ok(HPExpireTime { key, fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_hexpire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*7\r\n$7\r\nhexpire\r\n$3\r\nmap\r\n$2\r\n10\r\n$2\r\nNX\r\n$6\r\nFIELDS\r\n$1\r\n1\r\n$1\r\na\r\n");
+        let result: HExpire = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.key, "map");
+        assert_eq!(result.seconds, 10);
+        assert!(result.condition.nx);
+        assert_eq!(result.fields, vec!["a".to_string()]);
+
+        buf.extend_from_slice(b"*5\r\n$4\r\nhttl\r\n$3\r\nmap\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n");
+        let err = HTtl::try_from(RespArray::decode(&mut buf)?).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR The `numfields` parameter must match the number of arguments").into()
+        );
+
+        buf.extend_from_slice(b"*4\r\n$8\r\nhpersist\r\n$3\r\nmap\r\n$1\r\n1\r\n$1\r\na\r\n");
+        assert!(HPersist::try_from(RespArray::decode(&mut buf)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_httl_hpersist_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"a"), Bytes::from_static(b"1"))])?;
+        let fields = vec![Bytes::from_static(b"a"), Bytes::from_static(b"x")];
+
+        let cmd = HExpire {
+            key: Bytes::from_static(b"map"),
+            seconds: 100,
+            condition: ExpireCondition::default(),
+            fields: fields.clone(),
+        };
+        assert_eq!(cmd.execute(&backend), integer_array(vec![1, -2]));
+        let cmd = HTtl { key: Bytes::from_static(b"map"), fields: fields.clone() };
+        assert_eq!(cmd.execute(&backend), integer_array(vec![100, -2]));
+        let cmd = HPersist { key: Bytes::from_static(b"map"), fields: fields.clone() };
+        assert_eq!(cmd.execute(&backend), integer_array(vec![1, -2]));
+        let cmd = HPTtl { key: Bytes::from_static(b"map"), fields: fields.clone() };
+        assert_eq!(cmd.execute(&backend), integer_array(vec![-1, -2]));
+
+        let cmd = HPExpire {
+            key: Bytes::from_static(b"map"),
+            milliseconds: -1,
+            condition: ExpireCondition::default(),
+            fields: fields.clone(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'hpexpire' command").into()
+        );
+        let cmd = HExpireAt {
+            key: Bytes::from_static(b"map"),
+            timestamp: 1,
+            condition: ExpireCondition::default(),
+            fields,
+        };
+        assert_eq!(cmd.execute(&backend), integer_array(vec![2, -2]));
+        assert!(!backend.exists(b"map"));
+        Ok(())
+    }
+}
@@ -1,52 +1,62 @@
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor,  HGet, HGetAll, HSet, RESP_OK};
-use crate::{RespArray, RespFrame};
+use bytes::Bytes;
+use crate::cmd::{
+    bulk_or_null, extract_args, extract_keys, frame_to_bytes, frame_to_key, frame_to_string, validate_command,
+    parse_float, parse_integer, CommandError, CommandExecutor, HDel, HExists, HGet, HGetAll, HIncrBy,
+    HIncrByFloat, HKeys, HLen, HMGet, HMSet, HRandField, HScan, HSet, HSetNx,
+    HStrLen, HVals, RESP_OK,
+};
+use crate::{RespArray, RespFrame, RespMap};
 use crate::BulkString;
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hget(&self.key, &self.field).unwrap_or_else(|| RespFrame::Null(crate::RespNull))
+        match backend.hget(&self.key, &self.field) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
-
-        match hmap {
-            Some(hmap) => {
-                let mut data = Vec::with_capacity(hmap.len()*2);
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    data.push((key,v.value().clone()));
-
-                }
-                if self.sort{
-                    data.sort_by(|a,b|a.0.cmp(&b.0))
-                }
-                let ret = data.into_iter().flat_map(|(k,v)|vec![BulkString::from(k).into(),v])
-                    .collect::<Vec<RespFrame>>();
-                RespArray::new(ret).into()
-            }
-            None => RespArray::new([]).into(),
+        match backend.hgetall(&self.key) {
+            Ok(data) => data
+                .into_iter()
+                .map(|(k, v)| (BulkString::from(k), BulkString::new(v).into()))
+                .collect::<RespMap>()
+                .into(),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for HSet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        match backend.hset(self.key, self.fields) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// HMSET 与 HSET 相同，只是回复 OK
+impl CommandExecutor for HMSet {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hset(self.key, self.fields) {
+            Ok(_) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
     }
 }
 impl TryFrom<RespArray> for HGet{
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value,&["hget"],2)?;
+        validate_command(&value,&["hget"])?;
         let mut args = extract_args(value,1)?.into_iter();
         match (args.next(),args.next()){
             (Some(RespFrame::BulkString(key)),Some(RespFrame::BulkString(field)))=>Ok(HGet{
-                key:String::from_utf8(key.0)?,
-                field:String::from_utf8(field.0)?
+                key:key.into_bytes(),
+                field:field.into_bytes()
             }),
             _=>Err(CommandError::InvalidArgument("Invalid key field".to_string()))
         }
@@ -56,12 +66,11 @@ impl TryFrom<RespArray> for HGet{
 impl TryFrom<RespArray> for HGetAll{
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value,&["hgetall"],1)?;
+        validate_command(&value,&["hgetall"])?;
         let mut args = extract_args(value,1)?.into_iter();
         match args.next(){
             Some(RespFrame::BulkString(key))=>Ok(HGetAll{
-                key:String::from_utf8(key.0)?,
-                sort:false
+                key:key.into_bytes(),
             }),
             _=>Err(CommandError::InvalidArgument("Invalid key ".to_string()))
         }
@@ -70,28 +79,336 @@ impl TryFrom<RespArray> for HGetAll{
 impl TryFrom<RespArray> for HSet{
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value,&["hset"],3)?;
-        let mut args =  extract_args(value,1)?.into_iter();
-        match (args.next(),args.next(),args.next()) {
-            (Some(RespFrame::BulkString(key)),Some(RespFrame::BulkString(field)),Some(value))=>Ok(HSet{
-                key:String::from_utf8(key.0)?,
-                field:String::from_utf8(field.0)?,
-                value
-            }),
-            _=>Err(CommandError::InvalidArgument("Invalid key field or value ".to_string()))
+        parse_field_values(value, "hset")
+    }
+}
+impl TryFrom<RespArray> for HMSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let HSet { key, fields } = parse_field_values(value, "hmset")?;
+        Ok(HMSet { key, fields })
+    }
+}
+// key field value [field value ...]，HSET 与 HMSET 共用
+fn parse_field_values(value: RespArray, name: &'static str) -> Result<HSet, CommandError> {
+    validate_command(&value, &[name])?;
+    if !value.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidNumberOfArguments(name.to_string()));
+    }
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = frame_to_key(args.next().ok_or(CommandError::InvalidNumberOfArguments(name.to_string()))?)?;
+    let mut fields = Vec::with_capacity(args.len() / 2);
+    while let (Some(field), Some(value)) = (args.next(), args.next()) {
+        fields.push((frame_to_key(field)?, frame_to_bytes(value)?));
+    }
+    Ok(HSet { key, fields })
+}
+
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(n) => RespFrame::Integer(n as i64),
+            Err(e) => e.into(),
         }
     }
+}
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hexists(&self.key, &self.field) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(n) => RespFrame::Integer(n as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hkeys(&self.key) {
+            Ok(keys) => RespArray::new(
+                keys.into_iter()
+                    .map(|k| BulkString::from(k).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hvals(&self.key) {
+            Ok(values) => RespArray::new(
+                values
+                    .into_iter()
+                    .map(|v| BulkString::new(v).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HMGet {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hmget(&self.key, &self.fields) {
+            Ok(values) => RespArray::new(values.into_iter().map(bulk_or_null).collect::<Vec<_>>()).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HSetNx {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hsetnx(self.key, self.field, self.value) {
+            Ok(applied) => RespFrame::Integer(applied as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HStrLen {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hstrlen(&self.key, &self.field) {
+            Ok(n) => RespFrame::Integer(n as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hincrby(self.key, self.field, self.increment) {
+            Ok(n) => RespFrame::Integer(n),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hincrbyfloat(self.key, self.field, self.increment) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hscan(&self.key, self.cursor, self.count, self.pattern.as_deref()) {
+            Ok((next, items)) => {
+                let items = items
+                    .into_iter()
+                    .flat_map(|(field, value)| {
+                        let value = (!self.novalues).then(|| BulkString::new(value).into());
+                        std::iter::once(BulkString::from(field).into()).chain(value)
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new([BulkString::from(next.to_string()).into(), RespArray::new(items).into()]).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for HRandField {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        // 不带 count 时返回单个字段，key 不存在时为 nil
+        let Some(count) = self.count else {
+            return match backend.hrandfield(&self.key, 1) {
+                Ok(mut fields) => bulk_or_null(fields.pop().map(|(field, _)| field)),
+                Err(e) => e.into(),
+            };
+        };
+        match backend.hrandfield(&self.key, count) {
+            Ok(fields) => {
+                let items = fields
+                    .into_iter()
+                    .flat_map(|(field, value)| {
+                        let value = self.with_values.then(|| BulkString::new(value).into());
+                        std::iter::once(BulkString::from(field).into()).chain(value)
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(items).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// key field [field ...]
+fn parse_key_fields(value: RespArray, name: &'static str) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    validate_command(&value, &[name])?;
+    let mut args = extract_keys(value, 1)?.into_iter();
+    let key = args.next().ok_or(CommandError::InvalidNumberOfArguments(name.to_string()))?;
+    Ok((key, args.collect()))
+}
+// key field
+fn parse_key_field(value: RespArray, name: &'static str) -> Result<(Bytes, Bytes), CommandError> {
+    let (key, mut fields) = parse_key_fields(value, name)?;
+    match fields.pop() {
+        Some(field) if fields.is_empty() => Ok((key, field)),
+        _ => Err(CommandError::InvalidNumberOfArguments(name.to_string())),
+    }
+}
+fn parse_key(value: RespArray, name: &'static str) -> Result<Bytes, CommandError> {
+    let (key, _) = parse_key_fields(value, name)?;
+    Ok(key)
+}
 
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hdel")?;
+        Ok(HDel { key, fields })
+    }
+}
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field(value, "hexists")?;
+        Ok(HExists { key, field })
+    }
+}
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(HLen { key: parse_key(value, "hlen")? })
+    }
+}
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(HKeys { key: parse_key(value, "hkeys")? })
+    }
+}
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(HVals { key: parse_key(value, "hvals")? })
+    }
+}
+impl TryFrom<RespArray> for HMGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields(value, "hmget")?;
+        Ok(HMGet { key, fields })
+    }
+}
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hsetnx"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(field), Some(value)) => Ok(HSetNx {
+                key: frame_to_key(key)?,
+                field: frame_to_key(field)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key field or value".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrby"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(field), Some(increment)) => Ok(HIncrBy {
+                key: frame_to_key(key)?,
+                field: frame_to_key(field)?,
+                increment: parse_integer(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key field or increment".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrbyfloat"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(field), Some(increment)) => Ok(HIncrByFloat {
+                key: frame_to_key(key)?,
+                field: frame_to_key(field)?,
+                increment: parse_float(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key field or increment".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hscan"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?;
+        let cursor = frame_to_string(args.next().ok_or(CommandError::SyntaxError)?)?
+            .parse()
+            .map_err(|_| CommandError::InvalidArgument("invalid cursor".to_string()))?;
+        let mut scan = HScan { key, cursor, pattern: None, count: 10, novalues: false };
+        while let Some(arg) = args.next() {
+            match frame_to_string(arg)?.to_ascii_lowercase().as_str() {
+                "match" => {
+                    scan.pattern = Some(frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?);
+                }
+                "count" => {
+                    let count = parse_integer(&args.next().ok_or(CommandError::SyntaxError)?)?;
+                    if count < 1 {
+                        return Err(CommandError::SyntaxError);
+                    }
+                    scan.count = count as usize;
+                }
+                "novalues" => scan.novalues = true,
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(scan)
+    }
+}
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hrandfield"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = frame_to_key(args.next().ok_or(CommandError::SyntaxError)?)?;
+        let count = args.next().map(|c| parse_integer(&c)).transpose()?;
+        let with_values = match args.next().map(frame_to_string).transpose()? {
+            Some(arg) if arg.eq_ignore_ascii_case("withvalues") => true,
+            Some(_) => return Err(CommandError::SyntaxError),
+            None => false,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        // 与 Redis 一致，只拒绝取绝对值（WITHVALUES 时再翻倍）会溢出的负数 count
+        let min = if with_values { -(i64::MAX / 2) } else { -i64::MAX };
+        if count.is_some_and(|c| c < min) {
+            return Err(CommandError::InvalidArgument("value is out of range".to_string()));
+        }
+        Ok(HRandField { key, count, with_values })
+    }
+}
+impl TryFrom<RespArray> for HStrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field(value, "hstrlen")?;
+        Ok(HStrLen { key, field })
+    }
 }
 
 
 #[cfg(test)]
-
 mod tests{
     use super::*;
     use anyhow::Result;
     use bytes::BytesMut;
     use crate::RespDecode;
+    use crate::cmd::RespProtocol;
 
     #[test]
     fn test_hget_from_resp_array()->Result<()>{
@@ -122,49 +439,142 @@ mod tests{
 
         let result: HSet = frame.try_into()?;
         assert_eq!(result.key, "map");
-        assert_eq!(result.field, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.fields, vec![(Bytes::from_static(b"hello"), Bytes::from_static(b"world"))]);
+        Ok(())
+    }
+    #[test]
+    fn test_variadic_hset_from_array()->Result<()>{
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$4\r\nHSET\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n");
+        let result: HSet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(result.fields.len(), 2);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$5\r\nhmset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let err = HMSet::try_from(RespArray::decode(&mut buf)?).unwrap_err();
+        assert_eq!(err.to_string(), "wrong number of arguments for 'hmset' command");
         Ok(())
     }
     #[test]
     fn test_hset_hget_hgetall_commands()->Result<()>{
         let backend = crate::Backend::new();
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: Bytes::from_static(b"map"),
+            fields: vec![(Bytes::from_static(b"hello"), Bytes::from_static(b"world"))],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
+        // 已存在的字段不计入新增数
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            key: Bytes::from_static(b"map"),
+            fields: vec![
+                (Bytes::from_static(b"hello"), Bytes::from_static(b"world")),
+                (Bytes::from_static(b"hello1"), Bytes::from_static(b"world1")),
+            ],
         };
-        cmd.execute(&backend);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = HMSet {
+            key: Bytes::from_static(b"map"),
+            fields: vec![(Bytes::from_static(b"hello1"), Bytes::from_static(b"world1"))],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
 
         let cmd = HGet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
+            key: Bytes::from_static(b"map"),
+            field: Bytes::from_static(b"hello"),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         let cmd = HGetAll {
-            key: "map".to_string(),
-            sort:true
+            key: Bytes::from_static(b"map"),
         };
-        let result = cmd.execute(&backend);
+        // hash 中字段无序，逐个检查
+        let RespFrame::Map(result) = cmd.execute(&backend) else {
+            panic!("expected map");
+        };
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.get(&b"hello".into()), Some(&BulkString::from("world").into()));
+        assert_eq!(result.get(&b"hello1".into()), Some(&BulkString::from("world1").into()));
+
+        // RESP2 客户端收到与 map 顺序一致的扁平数组
+        let expected = RespArray::new(result.iter().flat_map(|(k, v)| [k.clone(), v.clone()]).collect::<Vec<_>>());
+        assert_eq!(RespProtocol::Resp2.adapt(result.into()), Some(expected.into()));
+        Ok(())
+    }
+    #[test]
+    fn test_hash_family_commands()->Result<()>{
+        let backend = crate::Backend::new();
+        let parse = |bytes: &[u8]| -> Result<RespArray> { Ok(RespArray::decode(&mut BytesMut::from(bytes))?) };
+
+        let cmd: HSetNx = parse(b"*4\r\n$6\r\nhsetnx\r\n$3\r\nmap\r\n$1\r\na\r\n$3\r\none\r\n")?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"b"), Bytes::from_static(b"two"))])?;
+
+        let cmd = HSetNx { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"a"), value: Bytes::from_static(b"x") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = HLen { key: Bytes::from_static(b"map") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = HExists { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"b") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = HStrLen { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"a") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd: HMGet = parse(b"*4\r\n$5\r\nhmget\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\nz\r\n")?.try_into()?;
+        let expected = RespArray::new([BulkString::from("one").into(), RespFrame::Null(crate::RespNull)]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = HKeys { key: Bytes::from_static(b"map") };
+        let RespFrame::Array(keys) = cmd.execute(&backend) else { panic!("expected an array") };
+        assert_eq!(keys.len(), 2);
+        let cmd = HVals { key: Bytes::from_static(b"none") };
+        assert_eq!(cmd.execute(&backend), RespArray::new(Vec::new()).into());
 
+        let cmd: HIncrBy = parse(b"*4\r\n$7\r\nhincrby\r\n$3\r\nmap\r\n$1\r\nn\r\n$2\r\n-5\r\n")?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-5));
+        let cmd = HIncrByFloat { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"n"), increment: 0.25 };
+        assert_eq!(cmd.execute(&backend), BulkString::from("-4.75").into());
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"f"), Bytes::from_static(b"0.1"))])?;
+        let cmd = HIncrByFloat { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"f"), increment: 0.2 };
+        assert_eq!(cmd.execute(&backend), BulkString::from("0.3").into());
+        backend.hdel(b"map", &[Bytes::from_static(b"f")])?;
+        let cmd = HIncrBy { key: Bytes::from_static(b"map"), field: Bytes::from_static(b"a"), increment: 1 };
+        assert_eq!(
+            cmd.execute(&backend),
+            crate::SimpleError::new("ERR hash value is not an integer").into()
+        );
+        backend.hdel(b"map", &[Bytes::from_static(b"n")])?;
+
+        let cmd: HScan = parse(b"*6\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$1\r\na\r\n$8\r\nNOVALUES\r\n")?.try_into()?;
         let expected = RespArray::new([
-            BulkString::from("hello").into(),
-            BulkString::from("world").into(),
-            BulkString::from("hello1").into(),
-            BulkString::from("world1").into(),
+            BulkString::from("0").into(),
+            RespArray::new([BulkString::from("a").into()]).into(),
         ]);
-        assert_eq!(result, expected.into());
+        assert_eq!(cmd.execute(&backend), expected.into());
+        assert!(HScan::try_from(parse(b"*3\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$2\r\n-1\r\n")?).is_err());
+
+        let cmd = HRandField { key: Bytes::from_static(b"map"), count: Some(-3), with_values: true };
+        let RespFrame::Array(items) = cmd.execute(&backend) else { panic!("expected an array") };
+        assert_eq!(items.len(), 6);
+        let cmd = HRandField { key: Bytes::from_static(b"none"), count: None, with_values: false };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(crate::RespNull));
+        // 负数 count 没有额外的上限，只有取绝对值会溢出时才拒绝
+        let cmd: HRandField = parse(b"*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$8\r\n-2000000\r\n")?.try_into()?;
+        let RespFrame::Array(items) = cmd.execute(&backend) else { panic!("expected an array") };
+        assert_eq!(items.len(), 2_000_000);
+        let err = HRandField::try_from(parse(b"*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$20\r\n-9223372036854775808\r\n")?).unwrap_err();
+        assert_eq!(RespFrame::from(err), crate::SimpleError::new("ERR value is out of range").into());
+        let err = HRandField::try_from(parse(b"*4\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$20\r\n-4611686018427387904\r\n$10\r\nWITHVALUES\r\n")?).unwrap_err();
+        assert_eq!(RespFrame::from(err), crate::SimpleError::new("ERR value is out of range").into());
+        let cmd: HRandField = parse(b"*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$12\r\n100000000000\r\n")?.try_into()?;
+        let RespFrame::Array(items) = cmd.execute(&backend) else { panic!("expected an array") };
+        assert_eq!(items.len(), 2);
+
+        let cmd: HDel = parse(b"*4\r\n$4\r\nhdel\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\nb\r\n")?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(!backend.exists(b"map"));
         Ok(())
     }
-
-}
\ No newline at end of file
+}
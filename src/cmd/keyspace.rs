@@ -0,0 +1,249 @@
+use crate::backend::Backend;
+use crate::cmd::{
+    extract_keys, validate_command, CommandError, CommandExecutor, DbSize, Del, Exists,
+    RandomKey, Rename, RenameNx, Type, RESP_OK,
+};
+use crate::{BulkString, RespArray, RespFrame, RespNull, SimpleString};
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        SimpleString::new(backend.key_type(&self.key)).into()
+    }
+}
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let n = self.keys.iter().filter(|key| backend.del(key)).count();
+        RespFrame::Integer(n as i64)
+    }
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 重复的 key 会被重复计数，与 Redis 一致
+        let n = self.keys.iter().filter(|key| backend.exists(key)).count();
+        RespFrame::Integer(n as i64)
+    }
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.newkey) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.renamenx(&self.key, self.newkey) {
+            Ok(renamed) => RespFrame::Integer(renamed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for RandomKey {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.random_key() {
+            Some(key) => BulkString::from(key).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["type"])?;
+        let mut args = extract_keys(value, 1)?.into_iter();
+        match args.next() {
+            Some(key) => Ok(Type { key }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["del"])?;
+        Ok(Del {
+            keys: extract_keys(value, 1)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["exists"])?;
+        Ok(Exists {
+            keys: extract_keys(value, 1)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["rename"])?;
+        let mut args = extract_keys(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(newkey)) => Ok(Rename { key, newkey }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or newkey".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["renamenx"])?;
+        let mut args = extract_keys(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(newkey)) => Ok(RenameNx { key, newkey }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or newkey".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dbsize"])?;
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for RandomKey {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["randomkey"])?;
+        Ok(RandomKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use crate::cmd::{Get, HGet};
+    use crate::{RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn wrong_type() -> RespFrame {
+        SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value").into()
+    }
+
+    #[test]
+    fn test_del_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\ndel\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Del = frame.try_into()?;
+        assert_eq!(result.keys, vec!["a".to_string(), "b".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(Bytes::from_static(b"map"), vec![(Bytes::from_static(b"f"), Bytes::from_static(b"v"))])?;
+        backend.set(Bytes::from_static(b"str"), Bytes::from_static(b"v"));
+
+        let cmd = Get { key: Bytes::from_static(b"map") };
+        assert_eq!(cmd.execute(&backend), wrong_type());
+        let cmd = HGet { key: Bytes::from_static(b"str"), field: Bytes::from_static(b"f") };
+        assert_eq!(cmd.execute(&backend), wrong_type());
+
+        let cmd = Type { key: Bytes::from_static(b"map") };
+        assert_eq!(cmd.execute(&backend), SimpleString::new("hash").into());
+        let cmd = Type { key: Bytes::from_static(b"none") };
+        assert_eq!(cmd.execute(&backend), SimpleString::new("none").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyspace_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"a"), Bytes::from_static(b"1"));
+        backend.hset(Bytes::from_static(b"b"), vec![(Bytes::from_static(b"f"), Bytes::from_static(b"v"))])?;
+
+        let cmd = Exists { keys: vec![Bytes::from_static(b"a"), Bytes::from_static(b"a"), Bytes::from_static(b"c")] };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = RenameNx { key: Bytes::from_static(b"a"), newkey: Bytes::from_static(b"b") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = Rename { key: Bytes::from_static(b"a"), newkey: Bytes::from_static(b"c") };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let cmd = Rename { key: Bytes::from_static(b"a"), newkey: Bytes::from_static(b"c") };
+        assert_eq!(cmd.execute(&backend), SimpleError::new("ERR no such key").into());
+
+        let cmd = Del { keys: vec![Bytes::from_static(b"b"), Bytes::from_static(b"c"), Bytes::from_static(b"d")] };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(RandomKey.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomkey_samples_live_keys() {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.set(Bytes::from(format!("k{}", i)), Bytes::from_static(b"v"));
+        }
+        let picked = (0..200)
+            .map(|_| match RandomKey.execute(&backend) {
+                RespFrame::BulkString(key) => key.into_bytes(),
+                frame => panic!("unexpected frame {:?}", frame),
+            })
+            .collect::<std::collections::HashSet<_>>();
+        assert!(picked.len() > 1);
+        assert!(picked.iter().all(|key| backend.exists(key)));
+
+        // 抽到的过期 key 会被删除并重新抽取
+        let soon = crate::backend::now_ms() + 20;
+        for i in 1..100 {
+            backend.expire_at(format!("k{}", i).as_bytes(), soon, Default::default());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        for _ in 0..10 {
+            assert_eq!(RandomKey.execute(&backend), BulkString::from("k0").into());
+        }
+    }
+
+    #[test]
+    fn test_rename_never_hides_the_value() {
+        // 值沿着 k0 -> k1 -> ... -> kN 依次改名，读者按顺序向后查找，
+        // RENAME 是原子的时候读者总能在当前或之后的某个名字上找到它
+        const N: usize = 20000;
+        let key = |i: usize| Bytes::from(format!("k{}", i));
+        let backend = Backend::new();
+        backend.set(key(0), Bytes::from_static(b"v"));
+        let renamer = {
+            let backend = backend.clone();
+            std::thread::spawn(move || {
+                for i in 0..N {
+                    backend.rename(&key(i), key(i + 1)).unwrap();
+                }
+            })
+        };
+        let mut cur = 0;
+        while cur < N {
+            while !backend.exists(&key(cur)) {
+                cur += 1;
+                assert!(cur <= N, "value disappeared during RENAME");
+            }
+        }
+        renamer.join().unwrap();
+    }
+}
@@ -1,26 +1,284 @@
-use crate::{RespArray, RespFrame, RespNull};
-use crate::backend::Backend;
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, Get, Set, RESP_OK};
+use bytes::Bytes;
+use crate::{BulkString, RespArray, RespFrame, RespMap, RespNull};
+use crate::backend::{lcs, now_ms, Backend, ExpireUpdate, SetCondition};
+use crate::cmd::{
+    bulk_or_null, extract_args, extract_keys, frame_to_bytes, frame_to_key, frame_to_string, parse_float, parse_integer, validate_command,
+    Append, CommandError, CommandExecutor, Decr, DecrBy, Get, GetDel, GetEx, GetRange, GetSet,
+    Incr, IncrBy, IncrByFloat, Lcs, MGet, MSet, MSetNx, PSetEx, Set, SetEx, SetExpiry, SetNx, SetRange, Strlen, SubStr,
+    RESP_OK,
+};
 
 impl CommandExecutor for Get{
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.get(&self.key).unwrap_or_else(|| RespFrame::Null(RespNull))
+        match backend.get(&self.key) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => e.into(),
+        }
     }
 }
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key,self.value);
+        let expire = match resolve_expiry(self.expiry, ExpireUpdate::Clear, "set") {
+            Ok(expire) => expire,
+            Err(e) => return e.into(),
+        };
+        match backend.set_with(self.key, self.value, self.condition, expire, self.get) {
+            // 带 GET 时总是返回旧值
+            Ok((_, old)) if self.get => bulk_or_null(old),
+            Ok((true, _)) => RESP_OK.clone(),
+            Ok((false, _)) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for SetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.set_with(self.key, self.value, SetCondition::Nx, ExpireUpdate::Clear, false) {
+            Ok((applied, _)) => RespFrame::Integer(applied as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for SetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        set_with_expiry(backend, self.key, self.value, SetExpiry::Ex(self.seconds), "setex")
+    }
+}
+impl CommandExecutor for PSetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        set_with_expiry(backend, self.key, self.value, SetExpiry::Px(self.milliseconds), "psetex")
+    }
+}
+impl CommandExecutor for GetSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.set_with(self.key, self.value, SetCondition::Always, ExpireUpdate::Clear, true) {
+            Ok((_, old)) => bulk_or_null(old),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire = match resolve_expiry(self.expiry, ExpireUpdate::Keep, "getex") {
+            Ok(expire) => expire,
+            Err(e) => return e.into(),
+        };
+        match backend.getex(&self.key, expire) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, 1)
+    }
+}
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, -1)
+    }
+}
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, self.increment)
+    }
+}
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.decrement.checked_neg() {
+            Some(delta) => incr_generic(backend, &self.key, delta),
+            None => CommandError::InvalidArgument("decrement would overflow".to_string()).into(),
+        }
+    }
+}
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(&self.key, self.increment) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.append(&self.key, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for Strlen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for SubStr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        GetRange { key: self.key, start: self.start, end: self.end }.execute(backend)
+    }
+}
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.offset < 0 {
+            return CommandError::InvalidArgument("offset is out of range".to_string()).into();
+        }
+        match backend.setrange(&self.key, self.offset as usize, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+impl CommandExecutor for Lcs {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let mut values = backend.get_many(&[self.key1, self.key2]).into_iter();
+        let (a, b) = match (values.next(), values.next()) {
+            (Some(Ok(a)), Some(Ok(b))) => (a.unwrap_or_default(), b.unwrap_or_default()),
+            (Some(Err(e)), _) | (_, Some(Err(e))) => return e.into(),
+            _ => unreachable!("get_many returns one value per key"),
+        };
+        let (seq, matches) = match lcs(&a, &b, self.min_match_len) {
+            Ok(ret) => ret,
+            Err(e) => return e.into(),
+        };
+        if !self.idx {
+            return match self.len {
+                true => RespFrame::Integer(seq.len() as i64),
+                false => BulkString::new(seq).into(),
+            };
+        }
+        let matches = matches
+            .into_iter()
+            .map(|m| {
+                let mut item = vec![
+                    RespArray::new([(m.a.0 as i64).into(), (m.a.1 as i64).into()]).into(),
+                    RespArray::new([(m.b.0 as i64).into(), (m.b.1 as i64).into()]).into(),
+                ];
+                if self.with_match_len {
+                    item.push((m.len as i64).into());
+                }
+                RespArray::new(item).into()
+            })
+            .collect::<Vec<RespFrame>>();
+        RespMap::from_iter([
+            (BulkString::from("matches"), RespArray::new(matches).into()),
+            (BulkString::from("len"), (seq.len() as i64).into()),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for MGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let values = backend
+            .mget(&self.keys)
+            .into_iter()
+            .map(bulk_or_null)
+            .collect::<Vec<_>>();
+        RespArray::new(values).into()
+    }
+}
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.mset(self.pairs);
         RESP_OK.clone()
     }
 }
+impl CommandExecutor for MSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.msetnx(self.pairs) as i64)
+    }
+}
+
+fn incr_generic(backend: &Backend, key: &[u8], delta: i64) -> RespFrame {
+    match backend.incr_by(key, delta) {
+        Ok(n) => RespFrame::Integer(n),
+        Err(e) => e.into(),
+    }
+}
+
+fn set_with_expiry(
+    backend: &Backend,
+    key: Bytes,
+    value: Bytes,
+    expiry: SetExpiry,
+    name: &str,
+) -> RespFrame {
+    let expire = match resolve_expiry(Some(expiry), ExpireUpdate::Clear, name) {
+        Ok(expire) => expire,
+        Err(e) => return e.into(),
+    };
+    match backend.set_with(key, value, SetCondition::Always, expire, false) {
+        Ok(_) => RESP_OK.clone(),
+        Err(e) => e.into(),
+    }
+}
+
+// 把过期选项换算成绝对时间，非正数或溢出都视为非法
+fn resolve_expiry(
+    expiry: Option<SetExpiry>,
+    default: ExpireUpdate,
+    name: &str,
+) -> Result<ExpireUpdate, CommandError> {
+    let at = match expiry {
+        None => return Ok(default),
+        Some(SetExpiry::KeepTtl) => return Ok(ExpireUpdate::Keep),
+        Some(SetExpiry::Persist) => return Ok(ExpireUpdate::Clear),
+        Some(SetExpiry::Ex(n)) if n > 0 => n.checked_mul(1000).and_then(|ms| ms.checked_add(now_ms())),
+        Some(SetExpiry::Px(n)) if n > 0 => n.checked_add(now_ms()),
+        Some(SetExpiry::ExAt(n)) if n > 0 => n.checked_mul(1000),
+        Some(SetExpiry::PxAt(n)) if n > 0 => Some(n),
+        Some(_) => None,
+    };
+    at.map(ExpireUpdate::At).ok_or_else(|| {
+        CommandError::InvalidArgument(format!("invalid expire time in '{}' command", name))
+    })
+}
+
+// 解析 EX / PX / EXAT / PXAT 后面跟着的时间
+fn parse_expiry_value(
+    option: &str,
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<SetExpiry, CommandError> {
+    let n = parse_integer(&args.next().ok_or(CommandError::SyntaxError)?)?;
+    match option {
+        "ex" => Ok(SetExpiry::Ex(n)),
+        "px" => Ok(SetExpiry::Px(n)),
+        "exat" => Ok(SetExpiry::ExAt(n)),
+        "pxat" => Ok(SetExpiry::PxAt(n)),
+        _ => Err(CommandError::SyntaxError),
+    }
+}
+
 impl TryFrom<RespArray> for Get{
     type Error = CommandError;
     fn try_from(value:RespArray)->Result<Self,Self::Error>{
 
-        validate_command(&value,&["get"],1)?;
+        validate_command(&value,&["get"])?;
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
-            Some(RespFrame::BulkString(key))=> Ok(Get { key: String::from_utf8(key.0)? })
+            Some(RespFrame::BulkString(key))=> Ok(Get { key: key.into_bytes() })
             ,
             _ => Err(CommandError::InvalidArgument("Invalid key ".to_string()))
         }
@@ -29,22 +287,331 @@ impl TryFrom<RespArray> for Get{
 impl TryFrom<RespArray> for Set{
     type Error= CommandError;
     fn try_from(value:RespArray)->Result<Self,Self::Error>{
-        validate_command(&value,&["set"],2)?;
+        validate_command(&value,&["set"])?;
         let mut args = extract_args(value,1)?.into_iter();
-        match(args.next(),args.next()){
-            (Some(RespFrame::BulkString(key)),Some(value))=>Ok(
-                Set{
-                    key: String::from_utf8(key.0)?,
-                    value,
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(key @ RespFrame::BulkString(_)), Some(value)) => {
+                (frame_to_key(key)?, frame_to_bytes(value)?)
+            }
+            _ => return Err(CommandError::InvalidArgument("Invalid key of value".to_string())),
+        };
+        let mut set = Set {
+            key,
+            value,
+            condition: SetCondition::Always,
+            expiry: None,
+            get: false,
+        };
+        while let Some(arg) = args.next() {
+            let option = frame_to_string(arg)?.to_ascii_lowercase();
+            match option.as_str() {
+                "nx" if set.condition != SetCondition::Xx => set.condition = SetCondition::Nx,
+                "xx" if set.condition != SetCondition::Nx => set.condition = SetCondition::Xx,
+                "get" => set.get = true,
+                "keepttl" if set.expiry.is_none() => set.expiry = Some(SetExpiry::KeepTtl),
+                "ex" | "px" | "exat" | "pxat" if set.expiry.is_none() => {
+                    set.expiry = Some(parse_expiry_value(&option, &mut args)?)
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(set)
+    }
+}
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setnx"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(value)) => Ok(SetNx {
+                key: frame_to_key(key)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key of value".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for SetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setex"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(seconds), Some(value)) => Ok(SetEx {
+                key: frame_to_key(key)?,
+                seconds: parse_integer(&seconds)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key, seconds or value".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for PSetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["psetex"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(milliseconds), Some(value)) => Ok(PSetEx {
+                key: frame_to_key(key)?,
+                milliseconds: parse_integer(&milliseconds)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key, milliseconds or value".to_string(),
+            )),
+        }
+    }
+}
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getset"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(value)) => Ok(GetSet {
+                key: frame_to_key(key)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key of value".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getdel"])?;
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(key) => Ok(GetDel { key: frame_to_key(key)? }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getex"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(key) => frame_to_key(key)?,
+            None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let mut expiry = None;
+        while let Some(arg) = args.next() {
+            let option = frame_to_string(arg)?.to_ascii_lowercase();
+            match option.as_str() {
+                "persist" if expiry.is_none() => expiry = Some(SetExpiry::Persist),
+                "ex" | "px" | "exat" | "pxat" if expiry.is_none() => {
+                    expiry = Some(parse_expiry_value(&option, &mut args)?)
                 }
-            ),_=>Err(CommandError::InvalidArgument("Invalid key of value".to_string()))
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(GetEx { key, expiry })
+    }
+}
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incr"])?;
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(key) => Ok(Incr { key: frame_to_key(key)? }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decr"])?;
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(key) => Ok(Decr { key: frame_to_key(key)? }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrby"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(increment)) => Ok(IncrBy {
+                key: frame_to_key(key)?,
+                increment: parse_integer(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or increment".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decrby"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(decrement)) => Ok(DecrBy {
+                key: frame_to_key(key)?,
+                decrement: parse_integer(&decrement)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or decrement".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrbyfloat"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(increment)) => Ok(IncrByFloat {
+                key: frame_to_key(key)?,
+                increment: parse_float(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or increment".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(value)) => Ok(Append {
+                key: frame_to_key(key)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key or value".to_string())),
         }
     }
 }
+impl TryFrom<RespArray> for Strlen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["strlen"])?;
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(key) => Ok(Strlen { key: frame_to_key(key)? }),
+            None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+// key start end
+fn parse_range_args(value: RespArray, name: &'static str) -> Result<(Bytes, i64, i64), CommandError> {
+    validate_command(&value, &[name])?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(key), Some(start), Some(end)) => Ok((
+            frame_to_key(key)?,
+            parse_integer(&start)?,
+            parse_integer(&end)?,
+        )),
+        _ => Err(CommandError::InvalidArgument("Invalid key, start or end".to_string())),
+    }
+}
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, end) = parse_range_args(value, "getrange")?;
+        Ok(GetRange { key, start, end })
+    }
+}
+impl TryFrom<RespArray> for SubStr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, end) = parse_range_args(value, "substr")?;
+        Ok(SubStr { key, start, end })
+    }
+}
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setrange"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(offset), Some(value)) => Ok(SetRange {
+                key: frame_to_key(key)?,
+                offset: parse_integer(&offset)?,
+                value: frame_to_bytes(value)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key, offset or value".to_string())),
+        }
+    }
+}
+impl TryFrom<RespArray> for Lcs {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lcs"])?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key1, key2) = match (args.next(), args.next()) {
+            (Some(key1), Some(key2)) => (frame_to_key(key1)?, frame_to_key(key2)?),
+            _ => return Err(CommandError::InvalidArgument("Invalid keys".to_string())),
+        };
+        let mut lcs = Lcs {
+            key1,
+            key2,
+            len: false,
+            idx: false,
+            min_match_len: 0,
+            with_match_len: false,
+        };
+        while let Some(arg) = args.next() {
+            match frame_to_string(arg)?.to_ascii_lowercase().as_str() {
+                "len" => lcs.len = true,
+                "idx" => lcs.idx = true,
+                "withmatchlen" => lcs.with_match_len = true,
+                "minmatchlen" => {
+                    let n = parse_integer(&args.next().ok_or(CommandError::SyntaxError)?)?;
+                    lcs.min_match_len = n.max(0) as usize;
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        if lcs.len && lcs.idx {
+            return Err(CommandError::InvalidArgument(
+                "If you want both the length and indexes, please just use IDX.".to_string(),
+            ));
+        }
+        Ok(lcs)
+    }
+}
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["mget"])?;
+        Ok(MGet { keys: extract_keys(value, 1)? })
+    }
+}
+// key value [key value ...]
+fn parse_pairs(value: RespArray, name: &'static str) -> Result<Vec<(Bytes, Bytes)>, CommandError> {
+    validate_command(&value, &[name])?;
+    if value.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidNumberOfArguments(name.to_string()));
+    }
+    let mut args = extract_args(value, 1)?.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        pairs.push((frame_to_key(key)?, frame_to_bytes(value)?));
+    }
+    Ok(pairs)
+}
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSet { pairs: parse_pairs(value, "mset")? })
+    }
+}
+impl TryFrom<RespArray> for MSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSetNx { pairs: parse_pairs(value, "msetnx")? })
+    }
+}
 #[cfg(test)]
 mod tests{
     use bytes::BytesMut;
-    use crate::RespDecode;
+    use crate::{RespDecode, SimpleError};
     use anyhow::Result;
     use crate::backend::Backend;
     use crate::cmd::RESP_OK;
@@ -68,7 +635,7 @@ mod tests{
         let frame = RespArray::decode(&mut buf)?;
         let result:Set = frame.try_into()?;
         assert_eq!(result.key,"hello");
-        assert_eq!(result.value,RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.value,Bytes::from_static(b"world"));
         Ok(())
     }
 
@@ -76,18 +643,224 @@ mod tests{
     fn test_set_get_command()->Result<()>{
         let backend = Backend::new();
         let cmd = Set{
-            key:"hello".to_string(),
-            value:RespFrame::BulkString(b"world".into())
+            key:Bytes::from_static(b"hello"),
+            value:Bytes::from_static(b"world"),
+            condition: SetCondition::Always,
+            expiry: None,
+            get: false,
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = Get {
-            key: "hello".to_string(),
+            key: Bytes::from_static(b"hello"),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_options_from_resp_array()->Result<()>{
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$3\r\nSET\r\n$4\r\nlock\r\n$5\r\ntoken\r\n$2\r\nNX\r\n$2\r\nPX\r\n$5\r\n30000\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result:Set = frame.try_into()?;
+        assert_eq!(result.key,"lock");
+        assert_eq!(result.condition,SetCondition::Nx);
+        assert_eq!(result.expiry,Some(SetExpiry::Px(30000)));
+
+        buf.extend_from_slice(b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nNX\r\n$2\r\nXX\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let ret = Set::try_from(frame);
+        assert!(matches!(ret, Err(CommandError::SyntaxError)));
+
+        buf.extend_from_slice(b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nEX\r\n$1\r\nx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let ret = Set::try_from(frame);
+        assert!(matches!(ret, Err(CommandError::NotInteger)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_nx_xx_get_command(){
+        let backend = Backend::new();
+        let set = |value: &[u8], condition, expiry, get| Set {
+            key: Bytes::from_static(b"lock"),
+            value: Bytes::copy_from_slice(value),
+            condition,
+            expiry,
+            get,
+        };
+
+        let cmd = set(b"a", SetCondition::Xx, None, false);
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        let cmd = set(b"a", SetCondition::Nx, Some(SetExpiry::Px(30000)), false);
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let cmd = set(b"b", SetCondition::Nx, None, false);
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        assert!(backend.pexpiretime(b"lock") > 0);
+
+        let cmd = set(b"c", SetCondition::Always, Some(SetExpiry::KeepTtl), true);
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"a"));
+        assert!(backend.pexpiretime(b"lock") > 0);
+
+        let cmd = set(b"d", SetCondition::Always, None, false);
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.pexpiretime(b"lock"), -1);
+
+        let cmd = set(b"e", SetCondition::Always, Some(SetExpiry::Ex(0)), false);
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'set' command").into()
+        );
+    }
+
+    #[test]
+    fn test_set_companion_commands(){
+        let backend = Backend::new();
+        let cmd = SetNx { key: Bytes::from_static(b"k"), value: Bytes::from_static(b"1") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = SetNx { key: Bytes::from_static(b"k"), value: Bytes::from_static(b"2") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = GetSet { key: Bytes::from_static(b"k"), value: Bytes::from_static(b"3") };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"1"));
+
+        let cmd = SetEx { key: Bytes::from_static(b"k"), seconds: 100, value: Bytes::from_static(b"4") };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let cmd = GetEx { key: Bytes::from_static(b"k"), expiry: Some(SetExpiry::Persist) };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"4"));
+        assert_eq!(backend.pexpiretime(b"k"), -1);
+
+        let cmd = PSetEx { key: Bytes::from_static(b"k"), milliseconds: -1, value: Bytes::from_static(b"5") };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'psetex' command").into()
+        );
+
+        let cmd = GetDel { key: Bytes::from_static(b"k") };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"4"));
+        let cmd = GetDel { key: Bytes::from_static(b"k") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_incr_decr_commands(){
+        let backend = Backend::new();
+        let cmd = Incr { key: Bytes::from_static(b"n") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = IncrBy { key: Bytes::from_static(b"n"), increment: 10 };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = DecrBy { key: Bytes::from_static(b"n"), decrement: 20 };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-9));
+        let cmd = Decr { key: Bytes::from_static(b"n") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-10));
+        let cmd = Get { key: Bytes::from_static(b"n") };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"-10"));
+
+        let cmd = IncrBy { key: Bytes::from_static(b"n"), increment: i64::MIN };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR increment or decrement would overflow").into()
+        );
+        let cmd = DecrBy { key: Bytes::from_static(b"n"), decrement: i64::MIN };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR decrement would overflow").into()
+        );
+
+        backend.set(Bytes::from_static(b"s"), Bytes::from_static(b"abc"));
+        let cmd = Incr { key: Bytes::from_static(b"s") };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR value is not an integer or out of range").into()
+        );
+
+        let cmd = IncrByFloat { key: Bytes::from_static(b"f"), increment: 1.5 };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"1.5"));
+    }
+
+    #[test]
+    fn test_append_strlen_range_commands(){
+        let backend = Backend::new();
+        let cmd = Append { key: Bytes::from_static(b"s"), value: Bytes::from_static(b"Hello") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = Append { key: Bytes::from_static(b"s"), value: Bytes::from_static(b" World") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = Strlen { key: Bytes::from_static(b"s") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = GetRange { key: Bytes::from_static(b"s"), start: -5, end: -1 };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"World"));
+        let cmd = SetRange { key: Bytes::from_static(b"s"), offset: 6, value: Bytes::from_static(b"Redis") };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = SubStr { key: Bytes::from_static(b"s"), start: 0, end: -1 };
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"Hello Redis"));
+        let cmd = SetRange { key: Bytes::from_static(b"s"), offset: -1, value: Bytes::from_static(b"x") };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR offset is out of range").into()
+        );
+    }
+
+    #[test]
+    fn test_lcs_command()->Result<()>{
+        let backend = Backend::new();
+        backend.set(Bytes::from_static(b"key1"), Bytes::from_static(b"ohmytext"));
+        backend.set(Bytes::from_static(b"key2"), Bytes::from_static(b"mynewtext"));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nLCS\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n");
+        let cmd: Lcs = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::from(b"mytext"));
+
+        let cmd = Lcs {
+            key1: Bytes::from_static(b"key1"),
+            key2: Bytes::from_static(b"key2"),
+            len: false,
+            idx: true,
+            min_match_len: 4,
+            with_match_len: true,
+        };
+        let matches = RespArray::new([RespArray::new([
+            RespArray::new([4.into(), 7.into()]).into(),
+            RespArray::new([5.into(), 8.into()]).into(),
+            4.into(),
+        ])
+        .into()]);
+        let expected = RespMap::from_iter([
+            (BulkString::from("matches"), matches.into()),
+            (BulkString::from("len"), 6.into()),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_mget_commands()->Result<()>{
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n");
+        let cmd: MSet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let ret = MSet::try_from(RespArray::decode(&mut buf)?);
+        assert!(matches!(ret, Err(CommandError::InvalidNumberOfArguments(_))));
+
+        let cmd = MGet { keys: vec![Bytes::from_static(b"a"), Bytes::from_static(b"c"), Bytes::from_static(b"b")] };
+        let expected = RespArray::new([
+            RespFrame::from(b"1"),
+            RespFrame::Null(RespNull),
+            RespFrame::from(b"2"),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = MSetNx { pairs: vec![(Bytes::from_static(b"c"), Bytes::from_static(b"3")), (Bytes::from_static(b"a"), Bytes::from_static(b"0"))] };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = MSetNx { pairs: vec![(Bytes::from_static(b"c"), Bytes::from_static(b"3")), (Bytes::from_static(b"d"), Bytes::from_static(b"4"))] };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        Ok(())
+    }
 }
\ No newline at end of file
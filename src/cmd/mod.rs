@@ -1,27 +1,85 @@
 mod map;
 mod hmap;
+mod command;
+mod keyspace;
+mod expire;
+mod hexpire;
+mod bitmap;
+mod connection;
+mod reply;
+pub mod table;
+use std::borrow::Cow;
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
-use crate::{RespArray, RespError, RespFrame, SimpleString};
-use crate::backend::Backend;
+use crate::{BulkString, RespArray, RespError, RespFrame, RespNull, SimpleError, SimpleString};
+use crate::backend::{
+    format_f64, parse_f64, Backend, BitFieldOp, BitOpKind, BitUnit, ExpireCondition, SetCondition,
+};
 use lazy_static::lazy_static;
+
+pub use reply::RespProtocol;
 lazy_static! {
     static ref RESP_OK:RespFrame = SimpleString::new("OK").into();
 }
 #[derive(Error, Debug)]
 pub enum CommandError {
-    #[error("Invalid command:{0}")]
+    #[error("Invalid command: {0}")]
     InvalidCommand(String),
-    #[error("Invalid number of arguments:{0} ")]
-    InvalidNumberOfArguments(usize),
-    #[error("Invalid argument:{0}")]
-    InvalidArgument(String),
+    #[error("unknown command '{name}', with args beginning with: {args}")]
+    UnknownCommand { name: String, args: String },
+    #[error("wrong number of arguments for '{0}' command")]
+    InvalidNumberOfArguments(String),
+    #[error("unknown subcommand '{sub}'. Try {cmd} HELP.")]
+    UnknownSubcommand { cmd: String, sub: String },
     #[error("{0}")]
+    InvalidArgument(String),
+    #[error("Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("value is not an integer or out of range")]
+    NotInteger,
+    #[error("value is not a valid float")]
+    NotFloat,
+    #[error("syntax error")]
+    SyntaxError,
+    #[error("unsupported protocol version")]
+    NoProto,
+    #[error("invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("Protocol error: {0}")]
     RespError(#[from] RespError),
 
-    #[error("Utf8 error :{0}")]
+    #[error("Utf8 error: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
+
+impl CommandError {
+    /// Redis-style error code sent in front of the message, e.g. `ERR` or `WRONGTYPE`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::InvalidCommand(_)
+            | CommandError::UnknownCommand { .. }
+            | CommandError::UnknownSubcommand { .. }
+            | CommandError::InvalidNumberOfArguments(_)
+            | CommandError::InvalidArgument(_)
+            | CommandError::RespError(_)
+            | CommandError::NotInteger
+            | CommandError::NotFloat
+            | CommandError::SyntaxError
+            | CommandError::Utf8Error(_) => "ERR",
+            CommandError::WrongType => "WRONGTYPE",
+            CommandError::NoProto => "NOPROTO",
+            CommandError::WrongPass => "WRONGPASS",
+        }
+    }
+}
+
+// 错误以 SimpleError 的形式回复给客户端，连接保持不断开
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        SimpleError::new(format!("{} {}", e.code(), e)).into()
+    }
+}
 #[enum_dispatch]
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
@@ -31,11 +89,81 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    SetNx(SetNx),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
+    GetSet(GetSet),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    Append(Append),
+    Strlen(Strlen),
+    GetRange(GetRange),
+    SubStr(SubStr),
+    SetRange(SetRange),
+    Lcs(Lcs),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    BitCount(BitCount),
+    BitPos(BitPos),
+    BitOp(BitOp),
+    BitField(BitField),
+    BitFieldRo(BitFieldRo),
     HGet(HGet),
     HSet(HSet),
+    HMSet(HMSet),
     HGetALl(HGetAll),
-    // unrecognized command
-    Unrecognized(Unrecognized),
+    HDel(HDel),
+    HExists(HExists),
+    HLen(HLen),
+    HKeys(HKeys),
+    HVals(HVals),
+    HMGet(HMGet),
+    HSetNx(HSetNx),
+    HStrLen(HStrLen),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HScan(HScan),
+    HRandField(HRandField),
+    HExpire(HExpire),
+    HPExpire(HPExpire),
+    HExpireAt(HExpireAt),
+    HPExpireAt(HPExpireAt),
+    HTtl(HTtl),
+    HPTtl(HPTtl),
+    HPersist(HPersist),
+    HExpireTime(HExpireTime),
+    HPExpireTime(HPExpireTime),
+    Type(Type),
+    Del(Del),
+    Exists(Exists),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    DbSize(DbSize),
+    RandomKey(RandomKey),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    Persist(Persist),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    CommandList(CommandList),
+    CommandCount(CommandCount),
+    CommandHelp(CommandHelp),
+    CommandInfo(CommandInfo),
+    CommandDocs(CommandDocs),
+    CommandGetKeys(CommandGetKeys),
+    Hello(Hello),
     // Del,
     // Incr,
     // Decr,
@@ -49,31 +177,419 @@ pub enum Command {
 
 #[derive(Debug)]
 pub struct Get {
-    key: String,
+    key: Bytes,
 }
+// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+//   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
 #[derive(Debug)]
 pub struct Set {
-    key: String,
-    value: RespFrame,
+    key: Bytes,
+    value: Bytes,
+    condition: SetCondition,
+    expiry: Option<SetExpiry>,
+    get: bool,
+}
+/// SET / GETEX 等命令中的过期选项，执行时再换算成绝对时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpiry {
+    Ex(i64),
+    Px(i64),
+    ExAt(i64),
+    PxAt(i64),
+    KeepTtl,
+    Persist,
+}
+#[derive(Debug)]
+pub struct SetNx {
+    key: Bytes,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct SetEx {
+    key: Bytes,
+    seconds: i64,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct PSetEx {
+    key: Bytes,
+    milliseconds: i64,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct GetSet {
+    key: Bytes,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct GetDel {
+    key: Bytes,
+}
+// GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+//   PXAT unix-time-milliseconds | PERSIST]
+#[derive(Debug)]
+pub struct GetEx {
+    key: Bytes,
+    expiry: Option<SetExpiry>,
+}
+#[derive(Debug)]
+pub struct Incr {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct Decr {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct IncrBy {
+    key: Bytes,
+    increment: i64,
+}
+#[derive(Debug)]
+pub struct DecrBy {
+    key: Bytes,
+    decrement: i64,
+}
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: Bytes,
+    increment: f64,
+}
+#[derive(Debug)]
+pub struct Append {
+    key: Bytes,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct Strlen {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct GetRange {
+    key: Bytes,
+    start: i64,
+    end: i64,
+}
+// SUBSTR 是 GETRANGE 的旧名字
+#[derive(Debug)]
+pub struct SubStr {
+    key: Bytes,
+    start: i64,
+    end: i64,
+}
+#[derive(Debug)]
+pub struct SetRange {
+    key: Bytes,
+    offset: i64,
+    value: Bytes,
+}
+// LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
+#[derive(Debug)]
+pub struct Lcs {
+    key1: Bytes,
+    key2: Bytes,
+    len: bool,
+    idx: bool,
+    min_match_len: usize,
+    with_match_len: bool,
+}
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+#[derive(Debug)]
+pub struct SetBit {
+    key: Bytes,
+    offset: u64,
+    bit: u8,
+}
+#[derive(Debug)]
+pub struct GetBit {
+    key: Bytes,
+    offset: u64,
+}
+// BITCOUNT key [start end [BYTE | BIT]]
+#[derive(Debug)]
+pub struct BitCount {
+    key: Bytes,
+    range: Option<(i64, i64, BitUnit)>,
+}
+// BITPOS key bit [start [end [BYTE | BIT]]]
+#[derive(Debug)]
+pub struct BitPos {
+    key: Bytes,
+    bit: u8,
+    start: i64,
+    end: Option<i64>,
+    unit: BitUnit,
+}
+#[derive(Debug)]
+pub struct BitOp {
+    op: BitOpKind,
+    dest: Bytes,
+    keys: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct BitField {
+    key: Bytes,
+    ops: Vec<BitFieldOp>,
+}
+#[derive(Debug)]
+pub struct BitFieldRo {
+    key: Bytes,
+    ops: Vec<BitFieldOp>,
 }
 #[derive(Debug)]
 pub struct HGet {
-    key: String,
-    field: String,
+    key: Bytes,
+    field: Bytes,
 }
 #[derive(Debug)]
 pub struct HSet {
-    key: String,
-    field: String,
-    value: RespFrame,
+    key: Bytes,
+    fields: Vec<(Bytes, Bytes)>,
+}
+#[derive(Debug)]
+pub struct HMSet {
+    key: Bytes,
+    fields: Vec<(Bytes, Bytes)>,
 }
 #[derive(Debug)]
 pub struct HGetAll {
-    key: String,
-    sort: bool,
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct HDel {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HExists {
+    key: Bytes,
+    field: Bytes,
+}
+#[derive(Debug)]
+pub struct HLen {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct HKeys {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct HVals {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct HMGet {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HSetNx {
+    key: Bytes,
+    field: Bytes,
+    value: Bytes,
+}
+#[derive(Debug)]
+pub struct HIncrBy {
+    key: Bytes,
+    field: Bytes,
+    increment: i64,
+}
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: Bytes,
+    field: Bytes,
+    increment: f64,
+}
+// HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+#[derive(Debug)]
+pub struct HScan {
+    key: Bytes,
+    cursor: u64,
+    pattern: Option<Bytes>,
+    count: usize,
+    novalues: bool,
 }
+// HRANDFIELD key [count [WITHVALUES]]
 #[derive(Debug)]
-pub struct Unrecognized;
+pub struct HRandField {
+    key: Bytes,
+    count: Option<i64>,
+    with_values: bool,
+}
+// HEXPIRE key seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+#[derive(Debug)]
+pub struct HExpire {
+    key: Bytes,
+    seconds: i64,
+    condition: ExpireCondition,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HPExpire {
+    key: Bytes,
+    milliseconds: i64,
+    condition: ExpireCondition,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HExpireAt {
+    key: Bytes,
+    timestamp: i64,
+    condition: ExpireCondition,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HPExpireAt {
+    key: Bytes,
+    timestamp: i64,
+    condition: ExpireCondition,
+    fields: Vec<Bytes>,
+}
+// HTTL key FIELDS numfields field [field ...]
+#[derive(Debug)]
+pub struct HTtl {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HPTtl {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HPersist {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HExpireTime {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HPExpireTime {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct HStrLen {
+    key: Bytes,
+    field: Bytes,
+}
+#[derive(Debug)]
+pub struct Type {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<Bytes>,
+}
+#[derive(Debug)]
+pub struct Rename {
+    key: Bytes,
+    newkey: Bytes,
+}
+#[derive(Debug)]
+pub struct RenameNx {
+    key: Bytes,
+    newkey: Bytes,
+}
+#[derive(Debug)]
+pub struct DbSize;
+#[derive(Debug)]
+pub struct RandomKey;
+// EXPIRE key seconds [NX | XX | GT | LT]
+#[derive(Debug)]
+pub struct Expire {
+    key: Bytes,
+    seconds: i64,
+    condition: ExpireCondition,
+}
+#[derive(Debug)]
+pub struct PExpire {
+    key: Bytes,
+    milliseconds: i64,
+    condition: ExpireCondition,
+}
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: Bytes,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: Bytes,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+#[derive(Debug)]
+pub struct Ttl {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct PTtl {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct Persist {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct ExpireTime {
+    key: Bytes,
+}
+#[derive(Debug)]
+pub struct PExpireTime {
+    key: Bytes,
+}
+// COMMAND
+#[derive(Debug)]
+pub struct CommandList;
+// COMMAND COUNT
+#[derive(Debug)]
+pub struct CommandCount;
+// COMMAND HELP
+#[derive(Debug)]
+pub struct CommandHelp;
+// COMMAND INFO [command-name ...]
+#[derive(Debug)]
+pub struct CommandInfo {
+    names: Vec<String>,
+}
+// COMMAND DOCS [command-name ...]
+#[derive(Debug)]
+pub struct CommandDocs {
+    names: Vec<String>,
+}
+// COMMAND GETKEYS command [arg ...]
+#[derive(Debug)]
+pub struct CommandGetKeys {
+    args: Vec<RespFrame>,
+}
+// HELLO [protover [AUTH username password] [SETNAME clientname]]
+#[derive(Debug)]
+pub struct Hello {
+    // 未指定时沿用连接当前的协议，由连接在执行前填入
+    protocol: Option<RespProtocol>,
+    auth: Option<(String, String)>,
+    client_id: u64,
+}
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
     fn try_from(value: RespFrame) -> Result<Self, Self::Error> {
@@ -88,38 +604,174 @@ impl TryFrom<RespFrame> for Command {
 impl TryFrom<RespArray> for Command {
     type Error = CommandError;
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
-        match v.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                b"get" => Ok(Get::try_from(v)?.into()),
-                b"set" => Ok(Set::try_from(v)?.into()),
-                b"hget" => Ok(HGet::try_from(v)?.into()),
-                b"hset" => Ok(HSet::try_from(v)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(v)?.into()),
-                _ => Ok(Unrecognized.into()),
+        // 命令名不区分大小写，与 validate_command 保持一致
+        let name = match v.first() {
+            Some(RespFrame::BulkString(ref cmd)) => {
+                String::from_utf8_lossy(cmd).to_ascii_lowercase()
+            }
+            _ => {
+                return Err(CommandError::InvalidCommand(
+                    "Command must have a BulkString as the first argument".to_string(),
+                ))
+            }
+        };
+        // 先查命令表：未登记的命令直接报错，参数个数统一在 validate_command 中校验
+        if table::lookup(&name).is_none() {
+            return Err(unknown_command(&v));
+        }
+        match name.as_str() {
+            "get" => Ok(Get::try_from(v)?.into()),
+            "set" => Ok(Set::try_from(v)?.into()),
+            "setnx" => Ok(SetNx::try_from(v)?.into()),
+            "setex" => Ok(SetEx::try_from(v)?.into()),
+            "psetex" => Ok(PSetEx::try_from(v)?.into()),
+            "getset" => Ok(GetSet::try_from(v)?.into()),
+            "getdel" => Ok(GetDel::try_from(v)?.into()),
+            "getex" => Ok(GetEx::try_from(v)?.into()),
+            "incr" => Ok(Incr::try_from(v)?.into()),
+            "decr" => Ok(Decr::try_from(v)?.into()),
+            "incrby" => Ok(IncrBy::try_from(v)?.into()),
+            "decrby" => Ok(DecrBy::try_from(v)?.into()),
+            "incrbyfloat" => Ok(IncrByFloat::try_from(v)?.into()),
+            "append" => Ok(Append::try_from(v)?.into()),
+            "strlen" => Ok(Strlen::try_from(v)?.into()),
+            "getrange" => Ok(GetRange::try_from(v)?.into()),
+            "substr" => Ok(SubStr::try_from(v)?.into()),
+            "setrange" => Ok(SetRange::try_from(v)?.into()),
+            "lcs" => Ok(Lcs::try_from(v)?.into()),
+            "mget" => Ok(MGet::try_from(v)?.into()),
+            "mset" => Ok(MSet::try_from(v)?.into()),
+            "msetnx" => Ok(MSetNx::try_from(v)?.into()),
+            "setbit" => Ok(SetBit::try_from(v)?.into()),
+            "getbit" => Ok(GetBit::try_from(v)?.into()),
+            "bitcount" => Ok(BitCount::try_from(v)?.into()),
+            "bitpos" => Ok(BitPos::try_from(v)?.into()),
+            "bitop" => Ok(BitOp::try_from(v)?.into()),
+            "bitfield" => Ok(BitField::try_from(v)?.into()),
+            "bitfield_ro" => Ok(BitFieldRo::try_from(v)?.into()),
+            "hget" => Ok(HGet::try_from(v)?.into()),
+            "hset" => Ok(HSet::try_from(v)?.into()),
+            "hmset" => Ok(HMSet::try_from(v)?.into()),
+            "hgetall" => Ok(HGetAll::try_from(v)?.into()),
+            "hdel" => Ok(HDel::try_from(v)?.into()),
+            "hexists" => Ok(HExists::try_from(v)?.into()),
+            "hlen" => Ok(HLen::try_from(v)?.into()),
+            "hkeys" => Ok(HKeys::try_from(v)?.into()),
+            "hvals" => Ok(HVals::try_from(v)?.into()),
+            "hmget" => Ok(HMGet::try_from(v)?.into()),
+            "hsetnx" => Ok(HSetNx::try_from(v)?.into()),
+            "hstrlen" => Ok(HStrLen::try_from(v)?.into()),
+            "hincrby" => Ok(HIncrBy::try_from(v)?.into()),
+            "hincrbyfloat" => Ok(HIncrByFloat::try_from(v)?.into()),
+            "hscan" => Ok(HScan::try_from(v)?.into()),
+            "hrandfield" => Ok(HRandField::try_from(v)?.into()),
+            "hexpire" => Ok(HExpire::try_from(v)?.into()),
+            "hpexpire" => Ok(HPExpire::try_from(v)?.into()),
+            "hexpireat" => Ok(HExpireAt::try_from(v)?.into()),
+            "hpexpireat" => Ok(HPExpireAt::try_from(v)?.into()),
+            "httl" => Ok(HTtl::try_from(v)?.into()),
+            "hpttl" => Ok(HPTtl::try_from(v)?.into()),
+            "hpersist" => Ok(HPersist::try_from(v)?.into()),
+            "hexpiretime" => Ok(HExpireTime::try_from(v)?.into()),
+            "hpexpiretime" => Ok(HPExpireTime::try_from(v)?.into()),
+            "type" => Ok(Type::try_from(v)?.into()),
+            "del" => Ok(Del::try_from(v)?.into()),
+            "exists" => Ok(Exists::try_from(v)?.into()),
+            "rename" => Ok(Rename::try_from(v)?.into()),
+            "renamenx" => Ok(RenameNx::try_from(v)?.into()),
+            "dbsize" => Ok(DbSize::try_from(v)?.into()),
+            "randomkey" => Ok(RandomKey::try_from(v)?.into()),
+            "expire" => Ok(Expire::try_from(v)?.into()),
+            "pexpire" => Ok(PExpire::try_from(v)?.into()),
+            "expireat" => Ok(ExpireAt::try_from(v)?.into()),
+            "pexpireat" => Ok(PExpireAt::try_from(v)?.into()),
+            "ttl" => Ok(Ttl::try_from(v)?.into()),
+            "pttl" => Ok(PTtl::try_from(v)?.into()),
+            "persist" => Ok(Persist::try_from(v)?.into()),
+            "expiretime" => Ok(ExpireTime::try_from(v)?.into()),
+            "pexpiretime" => Ok(PExpireTime::try_from(v)?.into()),
+            "command" => match subcommand(&v, "command")?.as_deref() {
+                None => Ok(CommandList::try_from(v)?.into()),
+                Some("count") => Ok(CommandCount::try_from(v)?.into()),
+                Some("info") => Ok(CommandInfo::try_from(v)?.into()),
+                Some("docs") => Ok(CommandDocs::try_from(v)?.into()),
+                Some("getkeys") => Ok(CommandGetKeys::try_from(v)?.into()),
+                Some("help") => Ok(CommandHelp::try_from(v)?.into()),
+                // 命令表中登记了但还没有实现的子命令
+                Some(sub) => Err(CommandError::UnknownSubcommand {
+                    cmd: "COMMAND".to_string(),
+                    sub: sub.to_string(),
+                }),
             },
-            _ => Err(CommandError::InvalidCommand(
-                "Command must have a BulkString as the first argument".to_string(),
-            )),
+            "hello" => Ok(Hello::try_from(v)?.into()),
+            _ => Err(unknown_command(&v)),
         }
     }
 }
-impl CommandExecutor for Unrecognized {
-    fn execute(self, _: &Backend) -> RespFrame {
-        RESP_OK.clone()
+
+// 与 Redis 一致：命令名最多显示 128 字节，参数部分共 128 字节，每个参数截断到剩余的长度
+const UNKNOWN_COMMAND_PREVIEW_LEN: usize = 128;
+
+// ERR unknown command 'foo', with args beginning with: 'a' 'b'
+fn unknown_command(v: &RespArray) -> CommandError {
+    let name = v.first().map(arg_bytes).unwrap_or_default();
+    let name = &name[..name.len().min(UNKNOWN_COMMAND_PREVIEW_LEN)];
+    let mut args = Vec::new();
+    for arg in v.iter().skip(1) {
+        if args.len() >= UNKNOWN_COMMAND_PREVIEW_LEN {
+            break;
+        }
+        let arg = arg_bytes(arg);
+        let n = arg.len().min(UNKNOWN_COMMAND_PREVIEW_LEN - args.len());
+        args.push(b'\'');
+        args.extend_from_slice(&arg[..n]);
+        args.extend_from_slice(b"' ");
+    }
+    // 换行符在 SimpleError 编码时统一处理
+    CommandError::UnknownCommand {
+        name: String::from_utf8_lossy(name).into_owned(),
+        args: String::from_utf8_lossy(&args).into_owned(),
+    }
+}
+
+// 参数按字符串显示，其他类型不是合法的命令参数，显示为空
+fn arg_bytes(frame: &RespFrame) -> Cow<'_, [u8]> {
+    match frame {
+        RespFrame::BulkString(s) => Cow::Borrowed(s),
+        RespFrame::SimpleString(s) => Cow::Borrowed(s.as_bytes()),
+        RespFrame::Integer(n) => Cow::Owned(n.to_string().into_bytes()),
+        _ => Cow::Borrowed(&[]),
+    }
+}
+
+// 取出容器命令（如 COMMAND）的小写子命令名，未登记的子命令返回错误
+fn subcommand(v: &RespArray, parent: &str) -> Result<Option<String>, CommandError> {
+    match v.get(1) {
+        Some(RespFrame::BulkString(sub)) => {
+            let sub = String::from_utf8_lossy(sub).to_ascii_lowercase();
+            match table::lookup(&format!("{}|{}", parent, sub)) {
+                Some(_) => Ok(Some(sub)),
+                None => Err(CommandError::UnknownSubcommand {
+                    cmd: parent.to_ascii_uppercase(),
+                    sub,
+                }),
+            }
+        }
+        Some(_) => Err(CommandError::InvalidArgument(
+            "subcommand must be a BulkString".to_string(),
+        )),
+        None => Ok(None),
     }
 }
 
-fn validate_command(
-    value: &RespArray,
-    names: &[&'static str],
-    n_args: usize,
-) -> Result<(), CommandError> {
-    if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have exactly {} argument",
-            names.join(" "),
-            n_args
-        )));
+// 按命令表中的 arity 校验参数个数，并检查命令名（及子命令名）
+fn validate_command(value: &RespArray, names: &[&'static str]) -> Result<(), CommandError> {
+    let full_name = names.join("|");
+    let spec = table::lookup(&full_name).ok_or_else(|| {
+        CommandError::InvalidCommand(format!("{} is not in the command table", full_name))
+    })?;
+    if !spec.check_arity(value.len()) {
+        return Err(CommandError::InvalidNumberOfArguments(full_name));
     }
 
     for (i, name) in names.iter().enumerate() {
@@ -145,10 +797,78 @@ fn validate_command(
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
+// 把参数解析为整数，支持 BulkString 形式的数字
+fn parse_integer(frame: &RespFrame) -> Result<i64, CommandError> {
+    match frame {
+        RespFrame::Integer(n) => Ok(*n),
+        RespFrame::BulkString(s) => std::str::from_utf8(s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(CommandError::NotInteger),
+        _ => Err(CommandError::NotInteger),
+    }
+}
+fn parse_float(frame: &RespFrame) -> Result<f64, CommandError> {
+    match frame {
+        RespFrame::Double(f) if f.is_finite() => Ok(*f),
+        RespFrame::Integer(n) => Ok(*n as f64),
+        RespFrame::BulkString(s) => parse_f64(s).ok_or(CommandError::NotFloat),
+        _ => Err(CommandError::NotFloat),
+    }
+}
+// 把参数转换为 key 或字段名，按原始字节保存
+fn extract_keys(value: RespArray, start: usize) -> Result<Vec<Bytes>, CommandError> {
+    extract_args(value, start)?
+        .into_iter()
+        .map(frame_to_key)
+        .collect()
+}
+// 不存在的值回复 nil
+fn bulk_or_null(value: Option<Bytes>) -> RespFrame {
+    match value {
+        Some(value) => BulkString::from(value).into(),
+        None => RespFrame::Null(RespNull),
+    }
+}
+// 值参数按字节保存，RESP3 客户端发来的数字类型也转换为字符串形式
+fn frame_to_bytes(frame: RespFrame) -> Result<Bytes, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(detach(s.into_bytes())),
+        RespFrame::SimpleString(s) => Ok(s.0.into()),
+        RespFrame::Integer(n) => Ok(n.to_string().into()),
+        RespFrame::Double(f) => Ok(format_f64(f).into()),
+        _ => Err(CommandError::InvalidArgument("Invalid value".to_string())),
+    }
+}
+// key 和字段名是任意字节，不要求是 UTF-8
+fn frame_to_key(frame: RespFrame) -> Result<Bytes, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(detach(s.into_bytes())),
+        _ => Err(CommandError::InvalidArgument("Invalid argument".to_string())),
+    }
+}
+// 与 Redis 的 PROTO_MBULK_BIG_ARG 一致，不小于 32KB 的参数才直接引用读缓冲区
+const SHARED_ARG_MIN_LEN: usize = 32 * 1024;
+
+// 解码出的 bulk string 引用整块读缓冲区，小参数被长期保存时会让整块缓冲区无法释放，
+// 这里复制成独立的 Bytes；大参数本身占了缓冲区的大部分，继续共享
+fn detach(bytes: Bytes) -> Bytes {
+    if bytes.len() < SHARED_ARG_MIN_LEN {
+        Bytes::copy_from_slice(&bytes)
+    } else {
+        bytes
+    }
+}
+fn frame_to_string(frame: RespFrame) -> Result<String, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.to_vec())?),
+        _ => Err(CommandError::InvalidArgument("Invalid argument".to_string())),
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{RespDecode, RespNull};
+    use crate::{RespDecode, RespEncode, RespNull};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -168,4 +888,140 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_command_is_case_insensitive() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+
+        let cmd: Command = frame.try_into()?;
+        assert!(matches!(cmd, Command::Get(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_command() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nfoo\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+
+        let ret: RespFrame = Command::try_from(frame).unwrap_err().into();
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR unknown command 'foo', with args beginning with: 'a' 'b' ").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_command_truncates_args() -> Result<()> {
+        let long = "x".repeat(200);
+        let frame = RespArray::new([
+            BulkString::from("foo").into(),
+            BulkString::from("a\r\nb").into(),
+            RespArray::new([BulkString::from(long.clone()).into()]).into(),
+            BulkString::from(long.clone()).into(),
+            BulkString::from("never shown").into(),
+        ]);
+        let ret: RespFrame = Command::try_from(frame).unwrap_err().into();
+        let expected = format!(
+            "-ERR unknown command 'foo', with args beginning with: 'a  b' '' '{}' \r\n",
+            &long[..128 - "'a  b' '' ".len()]
+        );
+        assert_eq!(ret.encode(), expected.as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_subcommand_reply_is_one_line() -> Result<()> {
+        let frame = RespArray::new([
+            BulkString::from("command").into(),
+            BulkString::from("x\r\n+OK").into(),
+        ]);
+        let ret: RespFrame = Command::try_from(frame).unwrap_err().into();
+        assert_eq!(
+            ret.encode(),
+            b"-ERR unknown subcommand 'x  +ok'. Try COMMAND HELP.\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_error_to_frame() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nhget\r\n$3\r\nmap\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+
+        let err = Command::try_from(frame).unwrap_err();
+        let ret: RespFrame = err.into();
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR wrong number of arguments for 'hget' command").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_values_do_not_share_read_buffer() -> Result<()> {
+        let backend = Backend::new();
+        let big = "x".repeat(SHARED_ARG_MIN_LEN);
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nsmall\r\n$5\r\nvalue\r\n");
+        buf.extend_from_slice(format!("*3\r\n$3\r\nset\r\n$3\r\nbig\r\n${}\r\n{}\r\n", big.len(), big).as_bytes());
+        let range = buf.as_ptr_range();
+        let in_buffer = |b: &Bytes| range.contains(&b.as_ptr());
+        for _ in 0..2 {
+            let cmd = Command::try_from(RespArray::decode(&mut buf)?)?;
+            cmd.execute(&backend);
+        }
+
+        // 小的 key 和值复制出来，不再引用读缓冲区；大值保持零拷贝
+        let key = backend.keyspace.iter().map(|e| e.key().clone()).find(|k| k == "small").unwrap();
+        assert!(!in_buffer(&key));
+        assert!(!in_buffer(&backend.get(b"small")?.unwrap()));
+        assert!(in_buffer(&backend.get(b"big")?.unwrap()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands_never_see_half_an_mset() {
+        // 两个 key 总是由同一个 MSET 写入相同的值：LCS 同时读取两个 key，长度应始终为 5；
+        // 依次 GET a 和 b 时，MSET 先写 a，b 不会比之前读到的 a 更旧
+        const N: usize = 20000;
+        let backend = Backend::new();
+        let (a, b) = (Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+        let value = |i: usize| Bytes::from(format!("{:05}", i));
+        backend.mset(vec![(a.clone(), value(0)), (b.clone(), value(0))]);
+        let writer = {
+            let (backend, a, b) = (backend.clone(), a.clone(), b.clone());
+            std::thread::spawn(move || {
+                for i in 1..=N {
+                    let cmd = Command::from(MSet { pairs: vec![(a.clone(), value(i)), (b.clone(), value(i))] });
+                    cmd.execute(&backend);
+                }
+            })
+        };
+        let get = |key: &Bytes| -> usize {
+            let value = backend.get(key).unwrap().unwrap();
+            std::str::from_utf8(&value).unwrap().parse().unwrap()
+        };
+        loop {
+            let cmd = Command::from(Lcs {
+                key1: a.clone(),
+                key2: b.clone(),
+                len: true,
+                idx: false,
+                min_match_len: 0,
+                with_match_len: false,
+            });
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+            let first = get(&a);
+            assert!(get(&b) >= first);
+            if first == N {
+                break;
+            }
+        }
+        writer.join().unwrap();
+    }
+}
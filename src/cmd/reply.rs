@@ -0,0 +1,107 @@
+use crate::backend::format_f64;
+use crate::{BulkString, RespArray, RespFrame, RespNullBulkString, SimpleError};
+
+/// 连接使用的协议版本，新连接默认为 RESP2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RespProtocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl RespProtocol {
+    /// 命令统一按 RESP3 的类型构造回复（Map / Set / Double / Null / Boolean），
+    /// 发送前按连接的协议转换：RESP3 原样返回，RESP2 降级为等价的 RESP2 类型。
+    ///
+    /// RESP2 没有属性类型，属性帧被丢弃（返回 None），只发送它所修饰的回复
+    pub fn adapt(self, frame: RespFrame) -> Option<RespFrame> {
+        match self {
+            RespProtocol::Resp3 => Some(frame),
+            RespProtocol::Resp2 => to_resp2(frame),
+        }
+    }
+}
+
+fn to_resp2(frame: RespFrame) -> Option<RespFrame> {
+    let frame = match frame {
+        RespFrame::Null(_) => RespNullBulkString.into(),
+        RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+        RespFrame::Double(f) => BulkString::from(format_f64(f)).into(),
+        RespFrame::BigNumber(n) => BulkString::from(n.0).into(),
+        RespFrame::VerbatimString(s) => BulkString::new(s.data).into(),
+        // RESP2 的错误只能占一行
+        RespFrame::BulkError(e) => {
+            let msg = String::from_utf8_lossy(&e).replace(['\r', '\n'], " ");
+            SimpleError::new(msg).into()
+        }
+        // map 展开为 key value 交替的数组
+        RespFrame::Map(map) => flatten_pairs(map.into_pairs()),
+        RespFrame::Attribute(_) => return None,
+        RespFrame::Set(set) => flatten(set.0),
+        RespFrame::Push(push) => flatten(push.0),
+        RespFrame::Array(array) => flatten(array.0),
+        frame => frame,
+    };
+    Some(frame)
+}
+
+// 聚合类型中的属性帧不计入元素，直接去掉
+fn flatten(items: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(items.into_iter().filter_map(to_resp2).collect::<Vec<_>>()).into()
+}
+
+// key 或 value 是属性帧时不能直接去掉，否则会打乱 key value 的交替，用 null 占位
+fn flatten_pairs(pairs: Vec<(RespFrame, RespFrame)>) -> RespFrame {
+    let items = pairs
+        .into_iter()
+        .flat_map(|(k, v)| [k, v])
+        .map(|frame| to_resp2(frame).unwrap_or_else(|| RespNullBulkString.into()))
+        .collect::<Vec<_>>();
+    RespArray::new(items).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkError, RespAttribute, RespMap, RespNull, RespPush, RespSet, VerbatimString};
+
+    #[test]
+    fn test_adapt_to_resp2() {
+        let map: RespMap = [
+            (BulkString::from("b"), RespFrame::Null(RespNull)),
+            (BulkString::from("a"), RespSet::new([RespFrame::Boolean(true), 1.5.into()]).into()),
+        ]
+        .into_iter()
+        .collect();
+        let frame: RespFrame = RespArray::new([map.into()]).into();
+
+        assert_eq!(RespProtocol::Resp3.adapt(frame.clone()), Some(frame.clone()));
+        let expected = RespArray::new([RespArray::new([
+            BulkString::from("b").into(),
+            RespNullBulkString.into(),
+            BulkString::from("a").into(),
+            RespArray::new([RespFrame::Integer(1), BulkString::from("1.5").into()]).into(),
+        ])
+        .into()]);
+        assert_eq!(RespProtocol::Resp2.adapt(frame), Some(expected.into()));
+
+        let frame = BulkError::from("ERR bad\r\nthing").into();
+        assert_eq!(RespProtocol::Resp2.adapt(frame), Some(SimpleError::new("ERR bad  thing").into()));
+        let frame = RespPush::new([VerbatimString::new(*b"txt", "hi").into()]).into();
+        let expected = RespArray::new([BulkString::from("hi").into()]);
+        assert_eq!(RespProtocol::Resp2.adapt(frame), Some(expected.into()));
+    }
+
+    #[test]
+    fn test_attributes_are_dropped_in_resp2() {
+        let mut attrs = RespAttribute::new();
+        attrs.insert("ttl", 3600.into());
+        assert_eq!(RespProtocol::Resp2.adapt(attrs.clone().into()), None);
+        assert_eq!(RespProtocol::Resp3.adapt(attrs.clone().into()), Some(attrs.clone().into()));
+
+        // 聚合类型中修饰元素的属性也被去掉
+        let frame = RespArray::new([attrs.into(), RespFrame::Integer(1)]).into();
+        let expected = RespArray::new([RespFrame::Integer(1)]);
+        assert_eq!(RespProtocol::Resp2.adapt(frame), Some(expected.into()));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFlag {
+    Write,
+    ReadOnly,
+    Fast,
+    Blocking,
+    Admin,
+}
+
+impl CommandFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandFlag::Write => "write",
+            CommandFlag::ReadOnly => "readonly",
+            CommandFlag::Fast => "fast",
+            CommandFlag::Blocking => "blocking",
+            CommandFlag::Admin => "admin",
+        }
+    }
+}
+
+/// 一条命令的元数据，字段含义与 Redis 的 `COMMAND INFO` 一致。
+///
+/// `arity` 包含命令名本身，负数表示参数个数至少为 `-arity`；
+/// `first_key`/`last_key`/`step` 描述 key 在参数中的位置，`last_key` 为负数表示从末尾倒数。
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [CommandFlag],
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+    pub group: &'static str,
+    pub summary: &'static str,
+}
+
+use CommandFlag::*;
+
+// 子命令以 "command|subcommand" 的形式登记
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec { name: "get", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns the string value of a key." },
+    CommandSpec { name: "set", arity: -3, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist." },
+    CommandSpec { name: "setnx", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Set the string value of a key only when the key doesn't exist." },
+    CommandSpec { name: "setex", arity: 4, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Sets the string value and expiration time of a key." },
+    CommandSpec { name: "psetex", arity: 4, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Sets both string value and expiration time in milliseconds of a key." },
+    CommandSpec { name: "getset", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns the previous string value of a key after setting it to a new value." },
+    CommandSpec { name: "getdel", arity: 2, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns the string value of a key after deleting the key." },
+    CommandSpec { name: "getex", arity: -2, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns the string value of a key after setting its expiration time." },
+    CommandSpec { name: "incr", arity: 2, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Increments the integer value of a key by one." },
+    CommandSpec { name: "decr", arity: 2, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Decrements the integer value of a key by one." },
+    CommandSpec { name: "incrby", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Increments the integer value of a key by a number." },
+    CommandSpec { name: "decrby", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Decrements a number from the integer value of a key." },
+    CommandSpec { name: "incrbyfloat", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Increment the floating point value of a key by a number." },
+    CommandSpec { name: "append", arity: 3, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Appends a string to the value of a key. Creates the key if it doesn't exist." },
+    CommandSpec { name: "strlen", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns the length of a string value." },
+    CommandSpec { name: "getrange", arity: 4, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns a substring of the string stored at a key." },
+    CommandSpec { name: "substr", arity: 4, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Returns a substring from a string value." },
+    CommandSpec { name: "setrange", arity: 4, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "string", summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist." },
+    CommandSpec { name: "lcs", arity: -3, flags: &[ReadOnly], first_key: 1, last_key: 2, step: 1, group: "string", summary: "Finds the longest common substring." },
+    CommandSpec { name: "mget", arity: -2, flags: &[ReadOnly, Fast], first_key: 1, last_key: -1, step: 1, group: "string", summary: "Atomically returns the string values of one or more keys." },
+    CommandSpec { name: "mset", arity: -3, flags: &[Write], first_key: 1, last_key: -1, step: 2, group: "string", summary: "Atomically creates or modifies the string values of one or more keys." },
+    CommandSpec { name: "msetnx", arity: -3, flags: &[Write], first_key: 1, last_key: -1, step: 2, group: "string", summary: "Atomically modifies the string values of one or more keys only when all keys don't exist." },
+    CommandSpec { name: "setbit", arity: 4, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist." },
+    CommandSpec { name: "getbit", arity: 3, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Returns a bit value by offset." },
+    CommandSpec { name: "bitcount", arity: -2, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Counts the number of set bits (population counting) in a string." },
+    CommandSpec { name: "bitpos", arity: -3, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Finds the first set (1) or clear (0) bit in a string." },
+    CommandSpec { name: "bitop", arity: -4, flags: &[Write], first_key: 2, last_key: -1, step: 1, group: "bitmap", summary: "Performs bitwise operations on multiple strings, and stores the result." },
+    CommandSpec { name: "bitfield", arity: -2, flags: &[Write], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Performs arbitrary bitfield integer operations on strings." },
+    CommandSpec { name: "bitfield_ro", arity: -2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "bitmap", summary: "Performs arbitrary read-only bitfield integer operations on strings." },
+    CommandSpec { name: "hget", arity: 3, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the value of a field in a hash." },
+    CommandSpec { name: "hset", arity: -4, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Creates or modifies the value of a field in a hash." },
+    CommandSpec { name: "hmset", arity: -4, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Sets the values of multiple fields." },
+    CommandSpec { name: "hgetall", arity: 2, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns all fields and values in a hash." },
+    CommandSpec { name: "hdel", arity: -3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain." },
+    CommandSpec { name: "hexists", arity: 3, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Determines whether a field exists in a hash." },
+    CommandSpec { name: "hlen", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the number of fields in a hash." },
+    CommandSpec { name: "hkeys", arity: 2, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns all fields in a hash." },
+    CommandSpec { name: "hvals", arity: 2, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns all values in a hash." },
+    CommandSpec { name: "hmget", arity: -3, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the values of all fields in a hash." },
+    CommandSpec { name: "hsetnx", arity: 4, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Sets the value of a field in a hash only when the field doesn't exist." },
+    CommandSpec { name: "hstrlen", arity: 3, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the length of the value of a field." },
+    CommandSpec { name: "hincrby", arity: 4, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Increments the integer value of a field in a hash by a number." },
+    CommandSpec { name: "hincrbyfloat", arity: 4, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Increments the floating point value of a field by a number." },
+    CommandSpec { name: "hscan", arity: -3, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Iterates over fields and values of a hash." },
+    CommandSpec { name: "hrandfield", arity: -2, flags: &[ReadOnly], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns one or more random fields from a hash." },
+    CommandSpec { name: "hexpire", arity: -6, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Set expiry for hash field using relative time to expire (seconds)." },
+    CommandSpec { name: "hpexpire", arity: -6, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Set expiry for hash field using relative time to expire (milliseconds)." },
+    CommandSpec { name: "hexpireat", arity: -6, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Set expiry for hash field using an absolute Unix timestamp (seconds)." },
+    CommandSpec { name: "hpexpireat", arity: -6, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Set expiry for hash field using an absolute Unix timestamp (milliseconds)." },
+    CommandSpec { name: "httl", arity: -5, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the TTL in seconds of a hash field." },
+    CommandSpec { name: "hpttl", arity: -5, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the TTL in milliseconds of a hash field." },
+    CommandSpec { name: "hpersist", arity: -5, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Removes the expiration time for each specified field." },
+    CommandSpec { name: "hexpiretime", arity: -5, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the expiration time of a hash field as a Unix timestamp, in seconds." },
+    CommandSpec { name: "hpexpiretime", arity: -5, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "hash", summary: "Returns the expiration time of a hash field as a Unix timestamp, in msec." },
+    CommandSpec { name: "type", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Determines the type of value stored at a key." },
+    CommandSpec { name: "del", arity: -2, flags: &[Write], first_key: 1, last_key: -1, step: 1, group: "generic", summary: "Deletes one or more keys." },
+    CommandSpec { name: "exists", arity: -2, flags: &[ReadOnly, Fast], first_key: 1, last_key: -1, step: 1, group: "generic", summary: "Determines whether one or more keys exist." },
+    CommandSpec { name: "rename", arity: 3, flags: &[Write], first_key: 1, last_key: 2, step: 1, group: "generic", summary: "Renames a key and overwrites the destination." },
+    CommandSpec { name: "renamenx", arity: 3, flags: &[Write, Fast], first_key: 1, last_key: 2, step: 1, group: "generic", summary: "Renames a key only when the target key name doesn't exist." },
+    CommandSpec { name: "dbsize", arity: 1, flags: &[ReadOnly, Fast], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns the number of keys in the database." },
+    CommandSpec { name: "randomkey", arity: 1, flags: &[ReadOnly], first_key: 0, last_key: 0, step: 0, group: "generic", summary: "Returns a random key name from the database." },
+    CommandSpec { name: "expire", arity: -3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Sets the expiration time of a key in seconds." },
+    CommandSpec { name: "pexpire", arity: -3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Sets the expiration time of a key in milliseconds." },
+    CommandSpec { name: "expireat", arity: -3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Sets the expiration time of a key to a Unix timestamp." },
+    CommandSpec { name: "pexpireat", arity: -3, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Sets the expiration time of a key to a Unix milliseconds timestamp." },
+    CommandSpec { name: "ttl", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Returns the expiration time in seconds of a key." },
+    CommandSpec { name: "pttl", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Returns the expiration time in milliseconds of a key." },
+    CommandSpec { name: "persist", arity: 2, flags: &[Write, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Removes the expiration time of a key." },
+    CommandSpec { name: "expiretime", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Returns the expiration time of a key as a Unix timestamp." },
+    CommandSpec { name: "pexpiretime", arity: 2, flags: &[ReadOnly, Fast], first_key: 1, last_key: 1, step: 1, group: "generic", summary: "Returns the expiration time of a key as a Unix milliseconds timestamp." },
+    CommandSpec { name: "command", arity: -1, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns detailed information about all commands." },
+    CommandSpec { name: "command|count", arity: 2, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns a count of commands." },
+    CommandSpec { name: "command|info", arity: -2, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns information about one, multiple or all commands." },
+    CommandSpec { name: "command|docs", arity: -2, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns documentary information about one, multiple or all commands." },
+    CommandSpec { name: "command|help", arity: 2, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Returns helpful text about the different subcommands." },
+    CommandSpec { name: "command|getkeys", arity: -3, flags: &[], first_key: 0, last_key: 0, step: 0, group: "server", summary: "Extracts the key names from an arbitrary command." },
+    CommandSpec { name: "hello", arity: -1, flags: &[Fast], first_key: 0, last_key: 0, step: 0, group: "connection", summary: "Handshakes with the Redis server." },
+];
+
+lazy_static! {
+    static ref COMMANDS: HashMap<&'static str, &'static CommandSpec> =
+        COMMAND_TABLE.iter().map(|spec| (spec.name, spec)).collect();
+}
+
+/// 按小写名字查找命令，子命令使用 "command|subcommand"
+pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.get(name).copied()
+}
+
+impl CommandSpec {
+    pub fn check_arity(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity >= 0 {
+            argc == self.arity
+        } else {
+            argc >= -self.arity
+        }
+    }
+
+    pub fn has_flag(&self, flag: CommandFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn is_subcommand(&self) -> bool {
+        self.name.contains('|')
+    }
+
+    pub fn subcommands(&self) -> impl Iterator<Item = &'static CommandSpec> + '_ {
+        COMMAND_TABLE.iter().filter(move |spec| {
+            spec.name
+                .split_once('|')
+                .is_some_and(|(parent, _)| parent == self.name)
+        })
+    }
+
+    /// 根据 key 位置描述，返回 argv 中 key 所在的下标
+    pub fn key_indexes(&self, argc: usize) -> Vec<usize> {
+        if self.first_key <= 0 {
+            return vec![];
+        }
+        let argc = argc as i64;
+        let last = if self.last_key < 0 {
+            argc + self.last_key
+        } else {
+            self.last_key.min(argc - 1)
+        };
+        let step = self.step.max(1);
+        (self.first_key..=last)
+            .step_by(step as usize)
+            .map(|i| i as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_arity() {
+        let spec = lookup("get").unwrap();
+        assert!(spec.check_arity(2));
+        assert!(!spec.check_arity(3));
+
+        let spec = lookup("command|info").unwrap();
+        assert!(spec.check_arity(2));
+        assert!(spec.check_arity(5));
+        assert!(!spec.check_arity(1));
+    }
+
+    #[test]
+    fn test_key_indexes() {
+        let spec = CommandSpec {
+            name: "mset",
+            arity: -3,
+            flags: &[Write],
+            first_key: 1,
+            last_key: -1,
+            step: 2,
+            group: "string",
+            summary: "",
+        };
+        assert_eq!(spec.key_indexes(5), vec![1, 3]);
+        assert_eq!(lookup("hget").unwrap().key_indexes(3), vec![1]);
+        assert!(lookup("command").unwrap().key_indexes(3).is_empty());
+    }
+
+    #[test]
+    fn test_subcommands() {
+        let names: Vec<_> = lookup("command")
+            .unwrap()
+            .subcommands()
+            .map(|spec| spec.name)
+            .collect();
+        assert!(names.contains(&"command|info"));
+        assert!(lookup("get").unwrap().subcommands().next().is_none());
+    }
+}
@@ -1,25 +1,37 @@
 
+use std::time::Duration;
 use anyhow::Result;
 use tokio::net::TcpListener;
 use tracing::{info, warn};
-use simple_redis::{network, Backend, };
+use simple_redis::{network, Backend, ProtocolLimits};
 
 #[tokio::main]
 async fn main()->Result<()> {
     tracing_subscriber::fmt::init();
 
     info!("运行开始啦 ");
+    // 协议限制可以通过命令行调整，例如 --proto-max-bulk-len 1gb
+    let limits = ProtocolLimits::from_args(std::env::args().skip(1))?;
     let addr = "0.0.0.0:6379";
     println!("开始了");
     info!("Simple-Redis-server is listening on {}",addr);
     let listener = TcpListener::bind(addr).await?;
     let backend = Backend::new();
+    // 后台主动过期：定期清理已经过期但没有被访问到的 key
+    let expire_backend = backend.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(100));
+        loop {
+            interval.tick().await;
+            expire_backend.active_expire_cycle();
+        }
+    });
     loop {
         let (stream,raddr) = listener.accept().await?;
         let clone_backend = backend.clone();
         info!("Accepted connection from :{} ",raddr);
         tokio::spawn(async move {
-            match network::stream_handler(stream,clone_backend).await{
+            match network::stream_handler(stream,clone_backend,limits).await{
                 Ok(_)=>{
                     info!("Commection from {} is handled successfully",raddr);
                 }
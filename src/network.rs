@@ -1,76 +1,251 @@
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecode, RespEncode, RespError, RespFrame,
+    cmd::{Command, CommandError, CommandExecutor, RespProtocol},
+    decode_inline, Backend, ProtocolLimits, RespEncode, RespError, RespFrame, RespParser,
 };
 use anyhow::Result;
 use futures::SinkExt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::net::TcpStream;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
 use tracing::info;
 
-#[derive(Debug)]
-struct RespFrameCodec;
+// 每个连接的 id，HELLO 的回复中会带上
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Default)]
+struct RespFrameCodec {
+    // 连接协商的协议，编码时把 RESP3 独有的类型降级为 RESP2
+    protocol: RespProtocol,
+    // 跨多次 decode 保留解析进度，不完整的帧不会被重新扫描
+    parser: RespParser,
+}
+
+impl RespFrameCodec {
+    fn new(limits: ProtocolLimits) -> Self {
+        Self {
+            protocol: RespProtocol::default(),
+            parser: RespParser::with_limits(limits),
+        }
+    }
+}
 
 #[derive(Debug)]
 struct RedisRequest {
     frame: RespFrame,
     backend:Backend,
+    protocol: RespProtocol,
+    client_id: u64,
 }
 
 #[derive(Debug)]
 struct RedisResponse {
     frame: RespFrame,
+    // 发送这条回复时连接使用的协议
+    protocol: RespProtocol,
 }
 
-pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
+pub async fn stream_handler(stream: TcpStream, backend: Backend, limits: ProtocolLimits) -> Result<()> {
     // how to get a frame from the stream?
-    let mut framed = Framed::new(stream, RespFrameCodec);
+    let mut framed = Framed::new(stream, RespFrameCodec::new(limits));
+    let client_id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
     loop {
         match framed.next().await {
-            Some(Ok(frame)) => {
+            Some(Ok(Ok(frame))) => {
                 info!("Received frame: {:?}", frame);
                 let request = RedisRequest {
                     frame,
                     backend: backend.clone(),
+                    protocol: framed.codec().protocol,
+                    client_id,
                 };
-                let response = request_handler(request).await?;
+                let response = request_handler(request).await;
                 info!("Sending response: {:?}", response.frame);
+                framed.codec_mut().protocol = response.protocol;
                 framed.send(response.frame).await?;
             }
-            Some(Err(e)) => return Err(e.into()),
+            // 可恢复的协议错误：回复错误，连接继续可用
+            Some(Ok(Err(e))) => {
+                info!("Recoverable protocol error: {:?}", e);
+                framed.send(CommandError::from(e).into()).await?;
+            }
+            // 不可恢复的协议错误：先发送错误帧，再关闭连接
+            Some(Err(e)) => {
+                if let Some(resp_err) = e.downcast_ref::<RespError>() {
+                    let frame = CommandError::from(resp_err.clone()).into();
+                    framed.send(frame).await?;
+                }
+                return Err(e);
+            }
             None => return Ok(()),
         }
     }
 }
 
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+async fn request_handler(request: RedisRequest) -> RedisResponse {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame })
+    let mut protocol = request.protocol;
+    let frame = match Command::try_from(frame) {
+        Ok(mut cmd) => {
+            info!("Executing command: {:?}", cmd);
+            // HELLO 成功后切换协议，它自己的回复已经使用新协议
+            let negotiated = match &mut cmd {
+                Command::Hello(hello) => Some(hello.negotiate(request.client_id, protocol)),
+                _ => None,
+            };
+            let frame = cmd.execute(&backend);
+            if let (Some(negotiated), false) = (negotiated, matches!(frame, RespFrame::Error(_))) {
+                protocol = negotiated;
+            }
+            frame
+        }
+        Err(e) => e.into(),
+    };
+    RedisResponse { frame, protocol }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
-        let encoded = item.encode();
-        dst.extend_from_slice(&encoded);
+        // RESP2 连接上的属性帧不发送
+        let Some(frame) = self.protocol.adapt(item) else {
+            return Ok(());
+        };
+        dst.reserve(frame.encoded_len());
+        frame.encode_to(dst);
         Ok(())
     }
 }
 
 impl Decoder for RespFrameCodec {
-    type Item = RespFrame;
+    // 内层的 Err 表示可恢复的协议错误，对应的数据已从缓冲区中丢弃
+    type Item = Result<RespFrame, RespError>;
     type Error = anyhow::Error;
 
-    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<RespFrame>> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>> {
+        loop {
+            return match self.parser.decode(src) {
+                Ok(frame) => Ok(frame.map(Ok)),
+                // 不是 RESP 类型前缀时按 inline 命令解析（redis-cli、telnet 等）
+                Err(RespError::UnknownFrameType(_)) => match decode_inline(src) {
+                    // 空行直接忽略
+                    Ok(args) if args.is_empty() => continue,
+                    Ok(args) => Ok(Some(Ok(args.into()))),
+                    // 一直没有换行的超长 inline 请求，不再继续缓冲
+                    Err(RespError::NotComplete) if src.len() > self.parser.limits().max_inline_len => {
+                        Err(RespError::LimitExceeded("too big inline request".to_string()).into())
+                    }
+                    Err(RespError::NotComplete) => Ok(None),
+                    // 出错的行已被丢弃，之后的数据仍可继续解析
+                    Err(e) => Ok(Some(Err(e))),
+                },
+                Err(e) => Err(e.into()),
+            };
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RespArray, RespDecode, RespNull, SimpleError};
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_codec_decodes_inline_commands() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"\r\nSET a \"x y\"\r\nGET \"a\r\n+OK\r\nPI");
+
+        let set = RespArray::new([b"SET".into(), b"a".into(), b"x y".into()]);
+        assert_eq!(codec.decode(&mut buf)?, Some(Ok(set.into())));
+        assert_eq!(codec.decode(&mut buf)?, Some(Err(RespError::UnbalancedQuotes)));
+        assert_eq!(codec.decode(&mut buf)?, Some(Ok(RespFrame::from("OK"))));
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"NG\n");
+        let ping = RespArray::new([b"PING".into()]);
+        assert_eq!(codec.decode(&mut buf)?, Some(Ok(ping.into())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_codec_encodes_by_protocol() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::new();
+        codec.encode(RespFrame::Null(RespNull), &mut buf)?;
+        codec.encode(RespFrame::Boolean(true), &mut buf)?;
+        assert_eq!(&buf[..], b"$-1\r\n:+1\r\n");
+
+        let mut request = RedisRequest {
+            frame: parse_frame(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n")?,
+            backend: Backend::new(),
+            protocol: codec.protocol,
+            client_id: 1,
+        };
+        let response = request_handler(request).await;
+        assert_eq!(response.protocol, RespProtocol::Resp3);
+        codec.protocol = response.protocol;
+        buf.clear();
+        codec.encode(RespFrame::Null(RespNull), &mut buf)?;
+        assert_eq!(&buf[..], b"_\r\n");
+
+        // 失败的 HELLO 不改变协议
+        request = RedisRequest {
+            frame: parse_frame(b"*2\r\n$5\r\nhello\r\n$1\r\n9\r\n")?,
+            backend: Backend::new(),
+            protocol: codec.protocol,
+            client_id: 1,
+        };
+        let response = request_handler(request).await;
+        assert_eq!(response.protocol, RespProtocol::Resp3);
+        Ok(())
+    }
+
+    fn parse_frame(input: &[u8]) -> Result<RespFrame> {
+        Ok(RespFrame::decode(&mut BytesMut::from(input))?)
+    }
+
+    #[test]
+    fn test_codec_decodes_split_frames() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::new();
+        let input = b"*3\r\n$3\r\nset\r\n$1\r\na\r\n$5\r\nhello\r\n";
+        let (head, tail) = input.split_at(20);
+        buf.extend_from_slice(head);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(tail);
+        let set = RespArray::new([b"set".into(), b"a".into(), b"hello".into()]);
+        assert_eq!(codec.decode(&mut buf)?, Some(Ok(set.into())));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_enforces_limits() {
+        let limits = ProtocolLimits { max_bulk_len: 3, ..ProtocolLimits::default() };
+        let mut codec = RespFrameCodec::new(limits);
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nget\r\n$4\r\n"[..]);
+        let err = codec.decode(&mut buf).unwrap_err();
+        let err = err.downcast_ref::<RespError>().cloned().unwrap();
+        assert_eq!(
+            RespFrame::from(CommandError::from(err)),
+            SimpleError::new("ERR Protocol error: invalid bulk length").into()
+        );
+
+        let limits = ProtocolLimits { max_inline_len: 4, ..ProtocolLimits::default() };
+        let mut codec = RespFrameCodec::new(limits);
+        let mut buf = BytesMut::from(&b"PING"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b" a");
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_codec_fails_on_broken_length() {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*x\r\n$3\r\nget\r\n");
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
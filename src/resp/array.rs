@@ -1,10 +1,10 @@
 use std::ops::Deref;
 use bytes::{Buf, BytesMut};
-use super::{RespDecode, RespEncode, RespError, RespFrame, extract_fixed_data, BUF_CAP, parse_length, calc_total_length, CRLF_LEN};
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+use super::{RespDecode, RespEncode, RespError, RespFrame, extract_fixed_data, put_header, header_len, parse_length, calc_total_length, CRLF_LEN};
+#[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
 pub struct RespArray(pub(crate) Vec<RespFrame>);
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct RespNullArray;
 
 
@@ -12,13 +12,14 @@ pub struct RespNullArray;
 
 
 impl RespEncode for RespArray {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        buf.extend_from_slice(&format!("*{}\r\n", self.len()).into_bytes());
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'*', self.len());
         for frame in self.0 {
-            buf.extend_from_slice(&frame.encode());
+            frame.encode_to(buf);
         }
-        buf
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.iter().map(RespEncode::encoded_len).sum::<usize>()
     }
 }
 
@@ -56,8 +57,11 @@ impl RespDecode for RespNullArray {
     }
 }
 impl RespEncode for RespNullArray {
-    fn encode(self) -> Vec<u8> {
-        b"*-1\r\n".to_vec()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"*-1\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        5
     }
 }
 
@@ -78,7 +82,7 @@ impl RespArray {
 mod test{
    use super::*;
     use anyhow::Result;
-    use crate::BulkString;
+    use crate::{BulkString, RespMap, RespNull, RespNullBulkString, RespSet, VerbatimString};
 
     #[test]
     fn test_null_array_decode()->Result<()>{
@@ -118,6 +122,23 @@ mod test{
         assert_eq!(frame.encode(),b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
     }
     #[test]
+    fn test_array_encoded_len() {
+        let map: RespMap = [(BulkString::from("k"), RespFrame::Double(-1.5))].into_iter().collect();
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("x".repeat(12)).into(),
+            RespFrame::Integer(-1234567890),
+            RespSet::new([RespFrame::Boolean(true), RespNull.into()]).into(),
+            map.into(),
+            VerbatimString::new(*b"txt", "hi").into(),
+            RespNullBulkString.into(),
+        ]).into();
+        let len = frame.encoded_len();
+        let mut buf = BytesMut::from(&b"+OK\r\n"[..]);
+        frame.clone().encode_to(&mut buf);
+        assert_eq!(&buf[5..], frame.encode());
+        assert_eq!(buf.len() - 5, len);
+    }
+    #[test]
     fn test_calc_array_length()->Result<()>{
         let buf = b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
         let (end,len) = parse_length(buf,"*")?;
@@ -0,0 +1,81 @@
+use std::ops::{Deref, DerefMut};
+use bytes::{Buf, BytesMut};
+use super::{RespDecode, RespEncode, RespError, RespMap, calc_total_length, parse_length, CRLF_LEN, put_header, header_len};
+
+/// 附加在回复前面的辅助信息，格式与 map 相同；
+/// 客户端可以忽略它，继续读取紧随其后的真正回复
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Hash)]
+pub struct RespAttribute(pub(crate) RespMap);
+
+impl RespAttribute {
+    pub fn new() -> Self {
+        RespAttribute(RespMap::new())
+    }
+}
+
+// - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
+impl RespEncode for RespAttribute {
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'|', self.len());
+        self.0.encode_pairs(buf);
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.pairs_len()
+    }
+}
+
+impl RespDecode for RespAttribute {
+    const PREFIX: &'static str = "|";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let total_length = calc_total_length(buf, end, len, Self::PREFIX)?;
+        if buf.len() < total_length {
+            return Err(RespError::NotComplete);
+        }
+        buf.advance(end + CRLF_LEN);
+        Ok(RespAttribute(RespMap::decode_pairs(buf, len)?))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX)
+    }
+}
+
+impl Deref for RespAttribute {
+    type Target = RespMap;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for RespAttribute {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::{RespFrame, SimpleString};
+
+    #[test]
+    fn test_attribute_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"|1\r\n+ttl\r\n:3600\r\n:42\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let mut attrs = RespAttribute::new();
+        attrs.insert("ttl", 3600.into());
+        assert_eq!(frame, attrs.into());
+        // 属性之后是真正的回复
+        assert_eq!(RespFrame::decode(&mut buf)?, 42.into());
+        Ok(())
+    }
+    #[test]
+    fn test_attribute_encode() {
+        let mut attrs = RespAttribute::new();
+        attrs.insert("key", SimpleString::new("value").into());
+        let frame: RespFrame = attrs.into();
+        assert_eq!(frame.encode(), b"|1\r\n+key\r\n+value\r\n");
+    }
+}
@@ -0,0 +1,78 @@
+use std::ops::Deref;
+use bytes::BytesMut;
+use super::{RespDecode, RespEncode, RespError, extract_simple_frame_data, CRLF_LEN};
+
+/// 任意精度的整数，以十进制字符串保存
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+pub struct BigNumber(pub(crate) String);
+
+impl BigNumber {
+    /// 只接受可选的正负号加十进制数字
+    pub fn new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(&s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!("expect: BigNumber, got: {:?}", s)));
+        }
+        Ok(BigNumber(s))
+    }
+}
+
+impl Deref for BigNumber {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// - big number: "([+|-]<number>\r\n"
+impl RespEncode for BigNumber {
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"(");
+        buf.extend_from_slice(self.0.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        1 + self.0.len() + CRLF_LEN
+    }
+}
+
+impl RespDecode for BigNumber {
+    const PREFIX: &'static str = "(";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        let s = String::from_utf8_lossy(&buf[Self::PREFIX.len()..end]).into_owned();
+        let frame = BigNumber::new(s)?;
+        let _ = buf.split_to(end + CRLF_LEN);
+        Ok(frame)
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_big_number_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"(3492890328409238509324850943850943825024385\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, BigNumber::new("3492890328409238509324850943850943825024385")?.into());
+
+        buf.extend_from_slice(b"(12a\r\n");
+        assert!(matches!(BigNumber::decode(&mut buf), Err(RespError::InvalidFrame(_))));
+        Ok(())
+    }
+    #[test]
+    fn test_big_number_encode() -> Result<()> {
+        let frame: RespFrame = BigNumber::new("-123456789012345678901234567890")?.into();
+        assert_eq!(frame.encode(), b"(-123456789012345678901234567890\r\n");
+        Ok(())
+    }
+}
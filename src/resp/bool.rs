@@ -4,8 +4,11 @@ use super::{RespDecode, RespEncode, RespError,extract_fixed_data};
 
 //    - boolean: "#<t|f>\r\n"
 impl RespEncode for bool {
-    fn encode(self) -> Vec<u8> {
-        format!("#{}\r\n", if self { "t" } else { "f" }).into_bytes()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(if self { b"#t\r\n" } else { b"#f\r\n" });
+    }
+    fn encoded_len(&self) -> usize {
+        4
     }
 }
 
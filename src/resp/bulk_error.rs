@@ -0,0 +1,79 @@
+use std::ops::Deref;
+use bytes::{Buf, BytesMut};
+use super::{RespDecode, RespEncode, RespError, parse_length, put_header, header_len, CRLF_LEN};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+pub struct BulkError(pub(crate) Vec<u8>);
+
+impl BulkError {
+    pub fn new(s: impl Into<Vec<u8>>) -> Self {
+        BulkError(s.into())
+    }
+}
+
+// - bulk error: "!<length>\r\n<error>\r\n"
+impl RespEncode for BulkError {
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'!', self.len());
+        buf.extend_from_slice(&self);
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.len() + CRLF_LEN
+    }
+}
+
+impl RespDecode for BulkError {
+    const PREFIX: &'static str = "!";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let remained = &buf[end + CRLF_LEN..];
+        if remained.len() < len + CRLF_LEN {
+            return Err(RespError::NotComplete);
+        }
+        buf.advance(end + CRLF_LEN);
+        let data = buf.split_to(len + CRLF_LEN);
+        Ok(BulkError::new(data[..len].to_vec()))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN + len + CRLF_LEN)
+    }
+}
+
+impl Deref for BulkError {
+    type Target = Vec<u8>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<&str> for BulkError {
+    fn from(s: &str) -> Self {
+        BulkError(s.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_bulk_error_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"!21\r\nSYNTAX invalid syntax\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, BulkError::from("SYNTAX invalid syntax").into());
+
+        buf.extend_from_slice(b"!5\r\nERR");
+        assert_eq!(BulkError::decode(&mut buf).unwrap_err(), RespError::NotComplete);
+        Ok(())
+    }
+    #[test]
+    fn test_bulk_error_encode() {
+        let frame: RespFrame = BulkError::new("ERR\r\nx").into();
+        assert_eq!(frame.encode(), b"!6\r\nERR\r\nx\r\n");
+    }
+}
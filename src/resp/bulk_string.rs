@@ -1,28 +1,30 @@
 use std::ops::Deref;
-use bytes::{Buf, BytesMut};
-use super::{RespDecode, RespEncode, RespError, extract_fixed_data, parse_length, CRLF_LEN};
-#[derive(Debug, Clone, PartialEq,Eq, PartialOrd)]
-pub struct BulkString(pub(crate) Vec<u8>);
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+use bytes::{Buf, Bytes, BytesMut};
+use super::{RespDecode, RespEncode, RespError, extract_fixed_data, parse_length, put_header, header_len, CRLF_LEN};
+// 使用引用计数的 Bytes：解码时直接切分读缓冲区，存入和读出 keyspace 都不复制数据
+#[derive(Debug, Clone, PartialEq,Eq, PartialOrd, Hash)]
+pub struct BulkString(pub(crate) Bytes);
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct RespNullBulkString;
 
 impl BulkString {
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
-        BulkString(s.into())
+        BulkString(Bytes::from(s.into()))
+    }
+    pub fn into_bytes(self) -> Bytes {
+        self.0
     }
 }
 
 // - bulk string: "$<length>\r\n<data>\r\n"
 impl RespEncode for BulkString {
-    fn encode(self) -> Vec<u8> {
-        // Vec::with_capacity()方法创建一个具有指定容量的Vec<u8>，这样可以避免在push()时重新分配内存
-        let mut buf = Vec::with_capacity(self.len() + 16);
-        buf.extend_from_slice(&format!("${}\r\n", self.len()).into_bytes());
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'$', self.len());
         buf.extend_from_slice(&self);
         buf.extend_from_slice(b"\r\n");
-        buf
-        // format!("${}\r\n{}\r\n",self.len(),String::from_utf8(self).unwrap()).into_bytes()
-        // format!("${}\r\n{}\r\n",self.len(),String::from_utf8_lossy(&self)).into_bytes()
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.len() + CRLF_LEN
     }
 }
 
@@ -35,8 +37,8 @@ impl RespDecode for BulkString {
             return Err(RespError::NotComplete);
         }
         buf.advance(end + CRLF_LEN);
-        let data = buf.split_to(len + CRLF_LEN);
-        Ok(BulkString::new(data[..len].to_vec()))
+        let data = buf.split_to(len + CRLF_LEN).freeze();
+        Ok(BulkString(data.slice(..len)))
     }
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
@@ -46,8 +48,11 @@ impl RespDecode for BulkString {
 
 // - null bulk string: "$-1\r\n"
 impl RespEncode for RespNullBulkString {
-    fn encode(self) -> Vec<u8> {
-        b"$-1\r\n".to_vec()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"$-1\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        5
     }
 }
 impl RespDecode for RespNullBulkString {
@@ -62,7 +67,7 @@ impl RespDecode for RespNullBulkString {
     }
 }
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = [u8];
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -70,22 +75,27 @@ impl Deref for BulkString {
 
 impl From<&str> for BulkString {
     fn from(s: &str) -> Self {
-        BulkString(s.as_bytes().to_vec())
+        BulkString(Bytes::copy_from_slice(s.as_bytes()))
     }
 }
 impl From<String> for BulkString {
     fn from(value: String) -> Self {
-        BulkString(value.into_bytes())
+        BulkString(Bytes::from(value))
     }
 }
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
     }
 }
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
+    }
+}
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        BulkString(s)
     }
 }
 
@@ -118,6 +128,16 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_bulk_string_decode_shares_buffer()->Result<()>{
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$5\r\nhello\r\n");
+        let start = buf.as_ptr();
+        let frame = BulkString::decode(&mut buf)?;
+        // 数据直接指向原来的读缓冲区，没有复制
+        assert_eq!(frame.as_ptr(), start.wrapping_add(4));
+        Ok(())
+    }
+    #[test]
     fn test_null_bulk_string_decode()->Result<()>{
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b"$-1\r\n");
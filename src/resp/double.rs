@@ -1,18 +1,38 @@
+use std::fmt::{self, Write};
 use bytes::BytesMut;
 use super::{RespDecode, RespEncode, RespError,extract_simple_frame_data, CRLF_LEN};
 
 //  - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 impl RespEncode for f64 {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(32);
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
-            format!(",{:+e}\r\n", self)
-        } else {
-            let sign = if self < 0.0 { "" } else { "+" };
-            format!(",{}{}\r\n", sign, self)
-        };
-        buf.extend_from_slice(&ret.into_bytes());
-        buf
+    // 直接格式化进缓冲区，不经过 String
+    fn encode_to(self, buf: &mut BytesMut) {
+        // 写入 BytesMut 不会失败
+        let _ = write_double(self, buf);
+    }
+    // 用同样的格式只数长度，不分配内存
+    fn encoded_len(&self) -> usize {
+        let mut counter = LenCounter(0);
+        let _ = write_double(*self, &mut counter);
+        counter.0
+    }
+}
+
+fn write_double(f: f64, w: &mut impl Write) -> fmt::Result {
+    if f.abs() > 1e+8 || f.abs() < 1e-8 {
+        write!(w, ",{:+e}\r\n", f)
+    } else {
+        let sign = if f < 0.0 { "" } else { "+" };
+        write!(w, ",{}{}\r\n", sign, f)
+    }
+}
+
+// 只统计写入的字节数
+struct LenCounter(usize);
+
+impl Write for LenCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
     }
 }
 
@@ -50,6 +70,18 @@ mod tests {
         assert_eq!(frame.encode(),b",-1.23456e-9\r\n");
     }
     #[test]
+    fn test_double_encoded_len() {
+        let values = [
+            0.0, -0.0, 1e-8, -1.2345678901234567e-8, 99999999.99999999, -1.2345678901234567e8,
+            f64::MIN, f64::MAX, f64::MIN_POSITIVE, -5e-324, f64::NAN, f64::NEG_INFINITY,
+        ];
+        for f in values {
+            let mut buf = BytesMut::new();
+            f.encode_to(&mut buf);
+            assert_eq!(buf.len(), f.encoded_len(), "{:?}", buf);
+        }
+    }
+    #[test]
     fn test_double_decode() -> Result<()> {
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b",123.456\r\n");
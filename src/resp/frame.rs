@@ -1,5 +1,7 @@
+use std::hash::{Hash, Hasher};
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
+use crate::{BigNumber, BulkError, RespAttribute, RespPush, VerbatimString};
 use crate::{BulkString, RespArray, RespMap, RespNullArray, RespNullBulkString, RespSet, SimpleError, SimpleString, RespNull, RespDecode, RespError};
 
 
@@ -18,6 +20,35 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    BulkError(BulkError),
+    VerbatimString(VerbatimString),
+    BigNumber(BigNumber),
+    Push(RespPush),
+    Attribute(RespAttribute),
+}
+
+// 与派生的 PartialEq 保持一致：0.0 与 -0.0 相等，哈希值也必须相同。RespMap 用它给 key 建索引
+impl Hash for RespFrame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            RespFrame::SimpleString(v) => v.hash(state),
+            RespFrame::Error(v) => v.hash(state),
+            RespFrame::Integer(v) => v.hash(state),
+            RespFrame::BulkString(v) => v.hash(state),
+            RespFrame::NullBulkString(_) | RespFrame::NullArray(_) | RespFrame::Null(_) => {}
+            RespFrame::Array(v) => v.hash(state),
+            RespFrame::Boolean(v) => v.hash(state),
+            RespFrame::Double(v) => (if *v == 0.0 { 0.0 } else { *v }).to_bits().hash(state),
+            RespFrame::Map(v) => v.hash(state),
+            RespFrame::Set(v) => v.hash(state),
+            RespFrame::BulkError(v) => v.hash(state),
+            RespFrame::VerbatimString(v) => v.hash(state),
+            RespFrame::BigNumber(v) => v.hash(state),
+            RespFrame::Push(v) => v.hash(state),
+            RespFrame::Attribute(v) => v.hash(state),
+        }
+    }
 }
 
 impl RespDecode for RespFrame {
@@ -80,8 +111,28 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'!') => {
+                let frame = BulkError::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'=') => {
+                let frame = VerbatimString::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'(') => {
+                let frame = BigNumber::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'|') => {
+                let frame = RespAttribute::decode(buf)?;
+                Ok(frame.into())
+            }
             None=>Err(RespError::NotComplete),
-            _ => Err(RespError::InvalidFrameType(format!("expect_length:unknown frame type :{:?}", buf)))
+            Some(&&b) => Err(RespError::UnknownFrameType(b)),
 
         }
     }
@@ -103,7 +154,14 @@ impl RespDecode for RespFrame {
             Some(b'#') => bool::expect_length(buf),
             Some(b',') => f64::expect_length(buf),
             Some(b'_') => RespNull::expect_length(buf),
-            _ => Err(RespError::NotComplete)
+            Some(b'!') => BulkError::expect_length(buf),
+            Some(b'=') => VerbatimString::expect_length(buf),
+            Some(b'(') => BigNumber::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
+            Some(b'|') => RespAttribute::expect_length(buf),
+            None => Err(RespError::NotComplete),
+            // 嵌套在聚合类型中的未知类型无法确定长度，不可恢复
+            _ => Err(RespError::InvalidFrameType(format!("expect_length: unknown frame type: {:?}", buf))),
         }
     }
 }
@@ -118,14 +176,14 @@ impl From<&str> for RespFrame {
 
 impl From<&[u8]> for RespFrame {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
     }
 }
 
 
 impl<const N: usize> From<&[u8; N]> for RespFrame {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec()).into()
+        BulkString::from(s).into()
     }
 }
 
@@ -0,0 +1,136 @@
+use bytes::BytesMut;
+use super::{BulkString, RespArray, RespError, RespFrame};
+
+/// 解析一行 inline 命令（如 `SET a "hello world"\r\n`），转换为与 RESP 请求相同的数组。
+///
+/// 参数以空白分隔，引号的处理与 Redis 的 `sdssplitargs` 一致。
+/// 空行返回空数组；无论解析是否成功，这一行都会从缓冲区中移除。
+pub fn decode_inline(buf: &mut BytesMut) -> Result<RespArray, RespError> {
+    let pos = buf.iter().position(|&b| b == b'\n').ok_or(RespError::NotComplete)?;
+    let line = buf.split_to(pos + 1);
+    let line = line[..pos].strip_suffix(b"\r").unwrap_or(&line[..pos]);
+    let args = split_args(line)?
+        .into_iter()
+        .map(|arg| BulkString::new(arg).into())
+        .collect::<Vec<RespFrame>>();
+    Ok(RespArray::new(args))
+}
+
+fn split_args(mut line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+    let mut args = Vec::new();
+    loop {
+        line = trim_start(line);
+        if line.is_empty() {
+            return Ok(args);
+        }
+        let (arg, rest) = split_arg(line)?;
+        args.push(arg);
+        line = rest;
+    }
+}
+
+// 取出一个参数，返回参数和剩余部分；引号可以出现在参数中间，如 a"b c" 解析为 `ab c`
+fn split_arg(line: &[u8]) -> Result<(Vec<u8>, &[u8]), RespError> {
+    let mut arg = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        match line[i] {
+            b'"' | b'\'' => {
+                let quote = line[i];
+                i += 1;
+                loop {
+                    match line.get(i) {
+                        None => return Err(RespError::UnbalancedQuotes),
+                        Some(&c) if c == quote => break,
+                        Some(b'\\') if quote == b'"' => {
+                            let (c, len) = unescape(&line[i..]);
+                            arg.push(c);
+                            i += len;
+                        }
+                        Some(b'\\') if line.get(i + 1) == Some(&b'\'') => {
+                            arg.push(b'\'');
+                            i += 2;
+                        }
+                        Some(&c) => {
+                            arg.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                // 闭合的引号后面必须是空白或行尾
+                if line.get(i).is_some_and(|c| !c.is_ascii_whitespace()) {
+                    return Err(RespError::UnbalancedQuotes);
+                }
+            }
+            c if c.is_ascii_whitespace() => break,
+            c => {
+                arg.push(c);
+                i += 1;
+            }
+        }
+    }
+    Ok((arg, &line[i..]))
+}
+
+// 双引号中的转义，返回字符和占用的字节数；s 以反斜杠开头
+fn unescape(s: &[u8]) -> (u8, usize) {
+    match s.get(1) {
+        Some(b'x') if s.len() >= 4 && s[2].is_ascii_hexdigit() && s[3].is_ascii_hexdigit() => {
+            let hex = std::str::from_utf8(&s[2..4]).unwrap_or_default();
+            (u8::from_str_radix(hex, 16).unwrap_or_default(), 4)
+        }
+        Some(b'n') => (b'\n', 2),
+        Some(b'r') => (b'\r', 2),
+        Some(b't') => (b'\t', 2),
+        Some(b'b') => (0x08, 2),
+        Some(b'a') => (0x07, 2),
+        Some(&c) => (c, 2),
+        // 行尾的反斜杠，之后会因为缺少闭合引号而报错
+        None => (b'\\', 1),
+    }
+}
+
+fn trim_start(s: &[u8]) -> &[u8] {
+    let n = s.iter().take_while(|c| c.is_ascii_whitespace()).count();
+    &s[n..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn bulks(args: &[&[u8]]) -> RespArray {
+        RespArray::new(args.iter().map(|a| BulkString::new(*a).into()).collect::<Vec<RespFrame>>())
+    }
+
+    #[test]
+    fn test_inline_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"SET  a 1\r\nPING\n  \r\nGET");
+        assert_eq!(decode_inline(&mut buf)?, bulks(&[b"SET", b"a", b"1"]));
+        assert_eq!(decode_inline(&mut buf)?, bulks(&[b"PING"]));
+        assert_eq!(decode_inline(&mut buf)?, bulks(&[]));
+        assert_eq!(decode_inline(&mut buf).unwrap_err(), RespError::NotComplete);
+        assert_eq!(&buf[..], b"GET");
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_quotes() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"set \"hello world\" 'it\\'s' \"\\x41\\n\" a\"b c\"\r\n");
+        assert_eq!(
+            decode_inline(&mut buf)?,
+            bulks(&[b"set", b"hello world", b"it's", b"A\n", b"ab c"])
+        );
+
+        buf.extend_from_slice(b"set \"abc\r\nset \"a\"b\r\nping\r\n");
+        assert_eq!(decode_inline(&mut buf).unwrap_err(), RespError::UnbalancedQuotes);
+        assert_eq!(decode_inline(&mut buf).unwrap_err(), RespError::UnbalancedQuotes);
+        // 出错的行已被丢弃
+        assert_eq!(decode_inline(&mut buf)?, bulks(&[b"ping"]));
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
 use bytes::BytesMut;
-use super::{RespDecode, RespEncode, RespError, extract_simple_frame_data, CRLF_LEN};
+use super::{RespDecode, RespEncode, RespError, extract_simple_frame_data, put_decimal, decimal_len, CRLF_LEN};
 
 impl RespEncode for i64 {
-    fn encode(self) -> Vec<u8> {
-        let sign = if self < 0 { "" } else { "+" };
-        format!(":{}{}\r\n", sign, self).into_bytes()
+    fn encode_to(self, buf: &mut BytesMut) {
+        let sign = if self < 0 { b":-" } else { b":+" };
+        buf.extend_from_slice(sign);
+        put_decimal(buf, self.unsigned_abs());
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        2 + decimal_len(self.unsigned_abs()) + CRLF_LEN
     }
 }
 impl RespDecode for i64 {
@@ -1,27 +1,87 @@
-use std::collections::BTreeMap;
-use std::ops::{Deref, DerefMut};
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::sync::OnceLock;
 use bytes::{Buf, BytesMut};
-use super::{RespDecode, RespEncode, RespError, RespFrame, SimpleString, calc_total_length, parse_length, CRLF_LEN, BUF_CAP};
+use super::{RespDecode, RespEncode, RespError, RespFrame, calc_total_length, parse_length, CRLF_LEN, put_header, header_len};
 
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+// RESP3 的 map 实际上是有序的键值对列表：key 可以是任意 RespFrame，按插入或线上的顺序保存，
+// 编码时保留 key 自身的类型。insert 和 collect 会对 key 去重，重复的 key 原位替换 value；
+// 解码时原样保留线上的键值对，重复的 key 只有第一个能通过 get 取到
+#[derive(Debug, Clone, Default)]
+pub struct RespMap {
+    pairs: Vec<(RespFrame, RespFrame)>,
+    // key 的哈希值 -> 第一个具有该哈希值的键值对下标，哈希冲突时再向后线性查找
+    index: HashMap<u64, usize>,
+}
 impl RespMap {
     pub fn new() -> Self {
-        RespMap(BTreeMap::new())
+        RespMap::default()
+    }
+    // 原样保存键值对，不去重
+    pub(crate) fn from_pairs(pairs: Vec<(RespFrame, RespFrame)>) -> Self {
+        let mut index = HashMap::with_capacity(pairs.len());
+        for (i, (key, _)) in pairs.iter().enumerate() {
+            index.entry(key_hash(key)).or_insert(i);
+        }
+        RespMap { pairs, index }
+    }
+    pub(crate) fn into_pairs(self) -> Vec<(RespFrame, RespFrame)> {
+        self.pairs
+    }
+    // key 已存在时原位替换 value 并返回旧值，否则追加到末尾
+    pub fn insert(&mut self, key: impl Into<RespFrame>, value: RespFrame) -> Option<RespFrame> {
+        let key = key.into();
+        let hash = key_hash(&key);
+        match self.position(&key, hash) {
+            Some(i) => Some(std::mem::replace(&mut self.pairs[i].1, value)),
+            None => {
+                self.index.entry(hash).or_insert(self.pairs.len());
+                self.pairs.push((key, value));
+                None
+            }
+        }
+    }
+    pub fn get(&self, key: &RespFrame) -> Option<&RespFrame> {
+        self.position(key, key_hash(key)).map(|i| &self.pairs[i].1)
+    }
+    fn position(&self, key: &RespFrame, hash: u64) -> Option<usize> {
+        let &first = self.index.get(&hash)?;
+        // 哈希值相同的 key 只会出现在 first 及其之后
+        self.pairs[first..].iter().position(|(k, _)| k == key).map(|i| first + i)
+    }
+    pub(crate) fn encode_pairs(self, buf: &mut BytesMut) {
+        for (key, value) in self.pairs {
+            key.encode_to(buf);
+            value.encode_to(buf);
+        }
+    }
+    pub(crate) fn pairs_len(&self) -> usize {
+        self.iter().map(|(key, value)| key.encoded_len() + value.encoded_len()).sum()
+    }
+    // 调用前需确认 buf 中已有完整的 len 个键值对
+    pub(crate) fn decode_pairs(buf: &mut BytesMut, len: usize) -> Result<Self, RespError> {
+        let mut pairs = Vec::with_capacity(len);
+        for _ in 0..len {
+            let key = RespFrame::decode(buf)?;
+            let value = RespFrame::decode(buf)?;
+            pairs.push((key, value));
+        }
+        Ok(RespMap::from_pairs(pairs))
     }
 }
 
 //map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
 impl RespEncode for RespMap {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
-        for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
-            buf.extend_from_slice(&value.encode());
-        }
-        buf
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'%', self.len());
+        self.encode_pairs(buf);
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.pairs_len()
     }
 }
 
@@ -35,74 +95,147 @@ impl RespDecode for RespMap {
             return Err(RespError::NotComplete);
         }
         buf.advance(end + CRLF_LEN);
-        let mut frames = RespMap::new();
-        for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
-        }
-        Ok(frames)
+        RespMap::decode_pairs(buf, len)
     }
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
         calc_total_length(buf,end,len,Self::PREFIX)
     }
 }
-impl Default for RespMap {
-    fn default() -> Self {
-        RespMap::new()
+// 与逐个 insert 相同：按迭代顺序保存，重复的 key 保留第一次出现的位置和最后一次的 value
+impl<K: Into<RespFrame>> FromIterator<(K, RespFrame)> for RespMap {
+    fn from_iter<T: IntoIterator<Item = (K, RespFrame)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut map = RespMap {
+            pairs: Vec::with_capacity(iter.size_hint().0),
+            index: HashMap::with_capacity(iter.size_hint().0),
+        };
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
     }
 }
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = [(RespFrame, RespFrame)];
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.pairs
     }
 }
-impl DerefMut for RespMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+// 比较和哈希只看键值对，索引由键值对决定
+impl PartialEq for RespMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
     }
 }
+impl PartialOrd for RespMap {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.pairs.partial_cmp(&other.pairs)
+    }
+}
+impl Hash for RespMap {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pairs.hash(state);
+    }
+}
+
+// 进程内共用一个随机种子，避免构造出大量哈希冲突的 key
+fn key_hash(key: &RespFrame) -> u64 {
+    static STATE: OnceLock<RandomState> = OnceLock::new();
+    STATE.get_or_init(RandomState::new).hash_one(key)
+}
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Result;
-    use crate::BulkString;
+    use bytes::Bytes;
+    use crate::{BulkString, SimpleString};
 
     #[test]
     fn test_map_decode() -> Result<()> {
         let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"%2\r\n+set\r\n$5\r\nhello\r\n+set\r\n$5\r\nhello\r\n");
-        // buf.extend_from_slice(b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n$3\r\nbar\r\n");
-        //
+        buf.extend_from_slice(b"%2\r\n+set\r\n$5\r\nhello\r\n+get\r\n$5\r\nworld\r\n");
         let frame: RespMap = RespMap::decode(&mut buf)?;
         let mut map = RespMap::new();
-        map.insert("set".to_string(), BulkString::new(b"hello".to_vec()).into());
-        map.insert("set".to_string(), BulkString::new(b"hello".to_vec()).into());
+        map.insert("set", BulkString::new(b"hello".to_vec()).into());
+        map.insert("get", BulkString::new(b"world".to_vec()).into());
         assert_eq!(frame, map);
         Ok(())
+    }
+    #[test]
+    fn test_map_decode_any_key() -> Result<()> {
+        // key 可以是 BulkString、Integer 甚至 Array，顺序与线上一致
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"%3\r\n$1\r\nz\r\n:1\r\n:7\r\n+seven\r\n*1\r\n#t\r\n_\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let RespFrame::Map(map) = &frame else { panic!("expected map") };
+        assert_eq!(map[0], (BulkString::from("z").into(), 1.into()));
+        assert_eq!(map.get(&7.into()), Some(&SimpleString::new("seven").into()));
+        assert_eq!(map[2].0, crate::RespArray::new([true.into()]).into());
 
-        // let mut buf = BytesMut::new();
-        //
-        // let frame = RespMap::decode(&mut buf)?;
-        // let mut map = RespMap::new();
-        // map.insert(
-        //     "hello".to_string(),
-        //     BulkString::new(b"world".to_vec()).into(),
-        // );
-        // map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
-        // assert_eq!(frame, map);
-        //
-        // Ok(())
+        // 原样编码回去
+        assert_eq!(frame.encode(), b"%3\r\n$1\r\nz\r\n:+1\r\n:+7\r\n+seven\r\n*1\r\n#t\r\n_\r\n");
+        Ok(())
+    }
+    #[test]
+    fn test_map_insert_replaces() {
+        let mut map = RespMap::new();
+        assert_eq!(map.insert("b", 1.into()), None);
+        assert_eq!(map.insert("a", 2.into()), None);
+        assert_eq!(map.insert("b", 3.into()), Some(1.into()));
+        assert_eq!(&map[..], &[("b".into(), 3.into()), ("a".into(), 2.into())]);
+    }
+    #[test]
+    fn test_map_collect_dedups_like_insert() {
+        let map: RespMap = [("b", 1.into()), ("a", 2.into()), ("b", 3.into())].into_iter().collect();
+        let mut expected = RespMap::new();
+        expected.insert("b", 1.into());
+        expected.insert("a", 2.into());
+        expected.insert("b", 3.into());
+        assert_eq!(map, expected);
+        assert_eq!(&map[..], &[("b".into(), 3.into()), ("a".into(), 2.into())]);
+    }
+    #[test]
+    fn test_map_get_any_key() {
+        let nested: RespFrame = RespMap::from_iter([(1, RespFrame::Boolean(true))]).into();
+        let mut map = RespMap::new();
+        map.insert(RespFrame::Double(-0.0), 1.into());
+        map.insert(nested.clone(), 2.into());
+        for i in 0..1000 {
+            map.insert(i, i.into());
+        }
+        // 0.0 与 -0.0 相等，视为同一个 key
+        assert_eq!(map.insert(RespFrame::Double(0.0), 3.into()), Some(1.into()));
+        assert_eq!(map.get(&nested), Some(&2.into()));
+        assert_eq!(map.get(&999.into()), Some(&999.into()));
+        assert_eq!(map.get(&BulkString::from("999").into()), None);
+        assert_eq!(map.len(), 1002);
+    }
+    #[test]
+    fn test_map_decode_keeps_duplicate_keys() -> Result<()> {
+        let mut buf = BytesMut::from(&b"%2\r\n+a\r\n:1\r\n+a\r\n:2\r\n"[..]);
+        let mut map = RespMap::decode(&mut buf)?;
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&SimpleString::new("a").into()), Some(&1.into()));
+        assert_eq!(map.insert(SimpleString::new("a"), 3.into()), Some(1.into()));
+        assert_eq!(map[1].1, 2.into());
+        Ok(())
     }
     #[test]
     fn test_map_encode(){
         let mut map: RespMap = RespMap::new();
-        map.insert("key".to_string(),SimpleString::new("value".to_string()).into());
+        map.insert(BulkString::from("key"),SimpleString::new("value".to_string()).into());
+        map.insert("simple",SimpleString::new("value".to_string()).into());
         let frame:RespFrame = map.into();
-        assert_eq!(frame.encode(),b"%1\r\n+key\r\n+value\r\n");
+        assert_eq!(frame.encode(),b"%2\r\n$3\r\nkey\r\n+value\r\n+simple\r\n+value\r\n");
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_map_binary_keys() -> Result<()> {
+        let map: RespMap = [(BulkString::from(Bytes::from_static(b"a\r\n\xff")), RespFrame::Integer(1))].into_iter().collect();
+        let mut buf = BytesMut::from(&map.clone().encode()[..]);
+        assert_eq!(RespMap::decode(&mut buf)?, map);
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 
 
 mod array;
+mod attribute;
+mod big_number;
+mod bulk_error;
 mod frame;
+mod inline;
 mod bool;
 mod bulk_string;
 mod double;
@@ -12,12 +16,18 @@ mod set;
 mod simple_error;
 mod simple_string;
 mod null;
+mod parser;
+mod push;
+mod verbatim_string;
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, BufMut, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
 pub use self::{array::{RespArray, RespNullArray}, bulk_string::{BulkString, RespNullBulkString},frame::{RespFrame}, map::RespMap, simple_error::SimpleError, simple_string::SimpleString,set::RespSet,null::RespNull};
+pub use self::inline::decode_inline;
+pub use self::parser::{ProtocolLimits, RespParser};
+pub use self::{attribute::RespAttribute, big_number::BigNumber, bulk_error::BulkError, push::RespPush, verbatim_string::VerbatimString};
 
 pub const BUF_CAP: usize = 4096;
 const CRLF: &[u8] = b"\r\n";
@@ -40,10 +50,22 @@ const CRLF_LEN: usize = CRLF.len();
     - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
     - map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
     - set: "~<number-of-elements>\r\n<element-1>...<element-n>"
+    - verbatim string: "=<length>\r\n<format>:<data>\r\n"
+    - big number: "([+|-]<number>\r\n"
+    - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+    - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
  */
 #[enum_dispatch]
-pub trait RespEncode {
-    fn encode(self) -> Vec<u8>;
+pub trait RespEncode: Sized {
+    /// 直接写入输出缓冲区，嵌套的元素不再各自分配 Vec
+    fn encode_to(self, buf: &mut BytesMut);
+    /// 编码后的字节数，写入前据此一次性预留空间；除 double 只给出上限外都是确切值
+    fn encoded_len(&self) -> usize;
+    fn encode(self) -> Vec<u8> {
+        let mut buf = BytesMut::with_capacity(self.encoded_len());
+        self.encode_to(&mut buf);
+        buf.into()
+    }
 }
 
 pub trait RespDecode: Sized {
@@ -52,16 +74,22 @@ pub trait RespDecode: Sized {
     fn expect_length(buf: &[u8]) -> Result<usize, RespError>;
 }
 
-#[derive(Error, Debug, PartialEq, Eq, )]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum RespError {
     #[error("Invalid frame: {0}")]
     InvalidFrame(String),
     #[error("Invalid frame type: {0}")]
     InvalidFrameType(String),
+    #[error("unknown frame type '{}'", *.0 as char)]
+    UnknownFrameType(u8),
     #[error("Invalid frame length： {0}")]
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("unbalanced quotes in request")]
+    UnbalancedQuotes,
+    #[error("{0}")]
+    LimitExceeded(String),
 
     #[error("Parse error: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -71,6 +99,36 @@ pub enum RespError {
     ParseFloatError(#[from] std::num::ParseFloatError),
 }
 // 公共函数
+// 写入 "<prefix><len>\r\n"，聚合类型和 bulk 类型的头部
+fn put_header(buf: &mut BytesMut, prefix: u8, len: usize) {
+    buf.put_u8(prefix);
+    put_decimal(buf, len as u64);
+    buf.put_slice(CRLF);
+}
+
+fn header_len(len: usize) -> usize {
+    1 + decimal_len(len as u64) + CRLF_LEN
+}
+
+// 不经过 format! 写入十进制数字
+fn put_decimal(buf: &mut BytesMut, mut n: u64) {
+    let mut digits = [0u8; 20];
+    let mut i = digits.len();
+    loop {
+        i -= 1;
+        digits[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    buf.put_slice(&digits[i..]);
+}
+
+fn decimal_len(n: u64) -> usize {
+    n.checked_ilog10().unwrap_or(0) as usize + 1
+}
+
 fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
     let end = extract_simple_frame_data(buf, prefix)?;
     let s = String::from_utf8_lossy(&buf[prefix.len()..end]);
@@ -94,7 +152,7 @@ fn calc_total_length(buf: &[u8],end:usize, len: usize, prefix: &str) -> Result<u
 
     // 根据前缀的不同，采取不同的处理逻辑。
     match prefix {
-        "*" | "~" => {
+        "*" | "~" | ">" => {
             // 对于多值回复或模糊匹配回复，查找下一个CRLF的位置，以确定当前消息的结束位置。
             //  find nth CRLF IN  THE buffer 在缓冲区中找到第n个CRLF
             for _ in 0..len {
@@ -105,13 +163,14 @@ fn calc_total_length(buf: &[u8],end:usize, len: usize, prefix: &str) -> Result<u
             Ok(total)
             // find_crlf(data, len).map(|end| len + CRLF_LEN + end).ok_or(RespError::NotComplete)
         }
-        "%" => {
+        "%" | "|" => {
             // 对于键值对回复，需要找到两个CRLF来确定一个键值对的结束，因此长度计算要考虑到这一点。
             //  find nth CRLF IN  THE buffer 在缓冲区中找到第n个CRLF
             // we need to find 2 CRLF for each key-value pair 我们需要为每个键值对找到2个CRLF
             // find_crlf(data, len * 2).map(|end| len + CRLF_LEN + end).ok_or(RespError::NotComplete)
             for _ in 0..len {
-                let  len = SimpleString::expect_length(data)?;
+                // key 可以是 SimpleString 或 BulkString
+                let  len = RespFrame::expect_length(data)?;
                 data = &data[len..];
                 total+=len;
 
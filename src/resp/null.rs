@@ -2,7 +2,7 @@ use bytes::BytesMut;
 
 use super::{extract_fixed_data, RespDecode, RespEncode, RespError};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Hash)]
 pub struct RespNull;
 
 
@@ -10,8 +10,11 @@ pub struct RespNull;
 
 // - null: "_\r\n"
 impl RespEncode for RespNull {
-    fn encode(self) -> Vec<u8> {
-        b"_\r\n".to_vec()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"_\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        3
     }
 }
 impl RespDecode for RespNull {
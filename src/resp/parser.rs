@@ -0,0 +1,490 @@
+use super::{
+    BigNumber, BulkError, BulkString, RespArray, RespAttribute, RespError, RespFrame, RespMap,
+    RespNull, RespNullArray, RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
+    VerbatimString, CRLF_LEN,
+};
+use bytes::{Buf, BytesMut};
+
+/// 解析客户端请求时的安全限制，超出时返回 `RespError::LimitExceeded`，连接随后关闭
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolLimits {
+    /// 单个 bulk string 的最大长度（proto-max-bulk-len）
+    pub max_bulk_len: usize,
+    /// 数组、集合、map 等聚合类型的最大元素个数
+    pub max_multibulk_len: usize,
+    /// 聚合类型的最大嵌套层数
+    pub max_depth: usize,
+    /// 尚未读到 CRLF 的一行允许的最大长度，同时限制 inline 命令
+    pub max_inline_len: usize,
+}
+
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_multibulk_len: 1024 * 1024,
+            max_depth: 32,
+            max_inline_len: 64 * 1024,
+        }
+    }
+}
+
+impl ProtocolLimits {
+    /// 从 redis-server 风格的命令行参数读取限制，例如 `--proto-max-bulk-len 1mb`，
+    /// 没有出现的限制保持默认值
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut limits = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                anyhow::bail!("unexpected argument '{}'", arg);
+            };
+            let Some(value) = args.next() else {
+                anyhow::bail!("missing value for '--{}'", name);
+            };
+            limits.set(name, &value)?;
+        }
+        Ok(limits)
+    }
+
+    /// 按配置项名称设置一个限制，长度类的取值支持 kb / mb / gb 等单位
+    pub fn set(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
+        let (field, n) = match name.to_ascii_lowercase().as_str() {
+            "proto-max-bulk-len" => (&mut self.max_bulk_len, parse_memory(value)),
+            "proto-max-multibulk-len" => (&mut self.max_multibulk_len, value.parse().ok()),
+            "proto-max-nesting-depth" => (&mut self.max_depth, value.parse().ok()),
+            "proto-inline-max-size" => (&mut self.max_inline_len, parse_memory(value)),
+            _ => anyhow::bail!("unknown config '{}'", name),
+        };
+        match n {
+            Some(n) if n > 0 => *field = n,
+            _ => anyhow::bail!("invalid value '{}' for '{}'", value, name),
+        }
+        Ok(())
+    }
+}
+
+// 与 Redis 的 memtoull 一致：k / m / g 是 1000 的倍数，kb / mb / gb 是 1024 的倍数，不区分大小写
+fn parse_memory(value: &str) -> Option<usize> {
+    let value = value.to_ascii_lowercase();
+    let units: [(&str, usize); 6] = [
+        ("kb", 1024),
+        ("mb", 1024 * 1024),
+        ("gb", 1024 * 1024 * 1024),
+        ("k", 1000),
+        ("m", 1000 * 1000),
+        ("g", 1000 * 1000 * 1000),
+    ];
+    let (digits, unit) = units
+        .iter()
+        .find_map(|&(suffix, unit)| Some((value.strip_suffix(suffix)?, unit)))
+        .unwrap_or((&value, 1));
+    digits.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+/// 可恢复的增量解析器：数据不完整时保留已经解析出的部分，下次从上次停下的位置继续。
+///
+/// 与 `RespFrame::decode` 不同，它不会预先计算整个帧的长度，每个字节只扫描一次；
+/// 已经完整的元素会立即从缓冲区中取走，大的数组分多次到达时不会被反复解析。
+#[derive(Debug, Default)]
+pub struct RespParser {
+    // 尚未完成的聚合类型，最后一个是最内层
+    stack: Vec<Aggregate>,
+    // 已读取长度行、正在等待内容的 bulk 类型
+    bulk: Option<(u8, usize)>,
+    // 缓冲区开头已经扫描过、确认不含 CRLF 的字节数
+    scanned: usize,
+    limits: ProtocolLimits,
+}
+
+#[derive(Debug)]
+struct Aggregate {
+    prefix: u8,
+    // 还需要的元素个数，map 的一对键值算两个
+    remaining: usize,
+    items: Vec<RespFrame>,
+}
+
+impl RespParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: ProtocolLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    pub fn limits(&self) -> &ProtocolLimits {
+        &self.limits
+    }
+
+    /// 解析一个完整的帧，数据不足时返回 `Ok(None)`。
+    ///
+    /// 顶层遇到未知的类型前缀时返回 `UnknownFrameType`，且不消耗任何数据，
+    /// 调用方可以按 inline 命令处理这一行。其他错误不可恢复。
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        loop {
+            let frame = match self.bulk {
+                Some((prefix, len)) => match self.take_bulk(buf, prefix, len)? {
+                    Some(frame) => frame,
+                    None => return Ok(None),
+                },
+                None => {
+                    let Some(&prefix) = buf.first() else {
+                        return Ok(None);
+                    };
+                    if !is_known_prefix(prefix) {
+                        return Err(match self.stack.is_empty() {
+                            true => RespError::UnknownFrameType(prefix),
+                            false => RespError::InvalidFrameType(format!(
+                                "unknown frame type '{}' in aggregate",
+                                prefix as char
+                            )),
+                        });
+                    }
+                    let Some(end) = self.find_line_end(buf) else {
+                        if buf.len() > self.limits.max_inline_len {
+                            return Err(RespError::LimitExceeded(
+                                "too big inline request".to_string(),
+                            ));
+                        }
+                        return Ok(None);
+                    };
+                    let line = buf.split_to(end + CRLF_LEN);
+                    match self.parse_line(&line[..end])? {
+                        Some(frame) => frame,
+                        // 开始了新的 bulk 或聚合类型，继续读取它的内容
+                        None => continue,
+                    }
+                }
+            };
+            if let Some(frame) = self.complete(frame)? {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    // 从上次扫描结束的位置继续查找 CRLF，返回 '\r' 的位置
+    fn find_line_end(&mut self, buf: &[u8]) -> Option<usize> {
+        // 上次末尾的 '\r' 可能与新到达的 '\n' 组成 CRLF
+        let start = self.scanned.saturating_sub(1);
+        match buf[start..].windows(CRLF_LEN).position(|w| w == b"\r\n") {
+            Some(pos) => {
+                self.scanned = 0;
+                Some(start + pos)
+            }
+            None => {
+                self.scanned = buf.len();
+                None
+            }
+        }
+    }
+
+    fn check_len(&self, prefix: u8, len: usize) -> Result<(), RespError> {
+        let is_bulk = matches!(prefix, b'$' | b'!' | b'=');
+        let (max, msg) = match is_bulk {
+            true => (self.limits.max_bulk_len, "invalid bulk length"),
+            false => (self.limits.max_multibulk_len, "invalid multibulk length"),
+        };
+        if len > max {
+            return Err(RespError::LimitExceeded(msg.to_string()));
+        }
+        if !is_bulk && self.stack.len() >= self.limits.max_depth {
+            return Err(RespError::LimitExceeded(
+                "too deeply nested request".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // 解析一行；返回 None 表示开始了一个还需要后续数据的类型
+    fn parse_line(&mut self, line: &[u8]) -> Result<Option<RespFrame>, RespError> {
+        let data = &line[1..];
+        let text = || String::from_utf8_lossy(data).into_owned();
+        let frame = match line[0] {
+            b'+' => SimpleString::new(text()).into(),
+            b'-' => SimpleError::new(text()).into(),
+            b':' => RespFrame::Integer(text().parse()?),
+            b',' => RespFrame::Double(text().parse()?),
+            b'(' => BigNumber::new(text())?.into(),
+            b'#' => match data {
+                b"t" => RespFrame::Boolean(true),
+                b"f" => RespFrame::Boolean(false),
+                _ => {
+                    return Err(RespError::InvalidFrame(format!(
+                        "expect: Boolean, got: {:?}",
+                        text()
+                    )))
+                }
+            },
+            b'_' if data.is_empty() => RespNull.into(),
+            b'_' => {
+                return Err(RespError::InvalidFrame(format!(
+                    "expect: Null, got: {:?}",
+                    text()
+                )))
+            }
+            prefix @ (b'$' | b'!' | b'=') => match parse_len(data)? {
+                None if prefix == b'$' => RespNullBulkString.into(),
+                None => return Err(RespError::InvalidFrameLength(-1)),
+                Some(len) => {
+                    self.check_len(prefix, len)?;
+                    self.bulk = Some((prefix, len));
+                    return Ok(None);
+                }
+            },
+            prefix @ (b'*' | b'~' | b'>' | b'%' | b'|') => match parse_len(data)? {
+                None if prefix == b'*' => RespNullArray.into(),
+                None => return Err(RespError::InvalidFrameLength(-1)),
+                Some(0) => finish(prefix, vec![])?,
+                Some(len) => {
+                    self.check_len(prefix, len)?;
+                    let remaining = if matches!(prefix, b'%' | b'|') {
+                        len * 2
+                    } else {
+                        len
+                    };
+                    self.stack.push(Aggregate {
+                        prefix,
+                        remaining,
+                        items: Vec::with_capacity(remaining.min(1024)),
+                    });
+                    return Ok(None);
+                }
+            },
+            prefix => return Err(RespError::UnknownFrameType(prefix)),
+        };
+        Ok(Some(frame))
+    }
+
+    // 读取 bulk 类型的内容，数据不足时什么都不消耗
+    fn take_bulk(
+        &mut self,
+        buf: &mut BytesMut,
+        prefix: u8,
+        len: usize,
+    ) -> Result<Option<RespFrame>, RespError> {
+        if buf.len() < len + CRLF_LEN {
+            return Ok(None);
+        }
+        if &buf[len..len + CRLF_LEN] != b"\r\n" {
+            return Err(RespError::InvalidFrame(
+                "bulk data is not terminated by CRLF".to_string(),
+            ));
+        }
+        self.bulk = None;
+        let data = buf.split_to(len).freeze();
+        buf.advance(CRLF_LEN);
+        let frame = match prefix {
+            b'$' => BulkString::from(data).into(),
+            b'!' => BulkError::new(data.to_vec()).into(),
+            _ => {
+                if len <= 3 || data[3] != b':' {
+                    return Err(RespError::InvalidFrame(format!(
+                        "expect: VerbatimString(<format>:<data>), got: {:?}",
+                        data
+                    )));
+                }
+                VerbatimString::new([data[0], data[1], data[2]], data[4..].to_vec()).into()
+            }
+        };
+        Ok(Some(frame))
+    }
+
+    // 把完整的元素放入外层的聚合类型，外层也完成时逐级向上；返回完成的顶层帧
+    fn complete(&mut self, mut frame: RespFrame) -> Result<Option<RespFrame>, RespError> {
+        loop {
+            let Some(parent) = self.stack.last_mut() else {
+                return Ok(Some(frame));
+            };
+            parent.items.push(frame);
+            parent.remaining -= 1;
+            if parent.remaining > 0 {
+                return Ok(None);
+            }
+            let parent = self.stack.pop().unwrap();
+            frame = finish(parent.prefix, parent.items)?;
+        }
+    }
+}
+
+fn finish(prefix: u8, items: Vec<RespFrame>) -> Result<RespFrame, RespError> {
+    let frame = match prefix {
+        b'*' => RespArray::new(items).into(),
+        b'~' => RespSet::new(items).into(),
+        b'>' => RespPush::new(items).into(),
+        b'%' => pairs(items).into(),
+        _ => RespAttribute(pairs(items)).into(),
+    };
+    Ok(frame)
+}
+
+// map 的 key 可以是任意类型，items 的个数一定是偶数
+fn pairs(items: Vec<RespFrame>) -> RespMap {
+    let mut items = items.into_iter();
+    let mut pairs = Vec::with_capacity(items.len() / 2);
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        pairs.push((key, value));
+    }
+    RespMap::from_pairs(pairs)
+}
+
+// 长度为 -1 时返回 None，表示 null
+fn parse_len(data: &[u8]) -> Result<Option<usize>, RespError> {
+    let len: i64 = String::from_utf8_lossy(data).parse()?;
+    match len {
+        -1 => Ok(None),
+        len if len < 0 => Err(RespError::InvalidFrameLength(len as isize)),
+        len => Ok(Some(len as usize)),
+    }
+}
+
+fn is_known_prefix(b: u8) -> bool {
+    matches!(
+        b,
+        b'+' | b'-'
+            | b':'
+            | b'$'
+            | b'*'
+            | b'_'
+            | b'#'
+            | b','
+            | b'%'
+            | b'~'
+            | b'!'
+            | b'='
+            | b'('
+            | b'>'
+            | b'|'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespDecode;
+    use anyhow::Result;
+
+    const NESTED: &[u8] =
+        b"*4\r\n$3\r\nset\r\n*2\r\n:+1\r\n_\r\n%2\r\n+k\r\n,1.5\r\n:3\r\n$1\r\nv\r\n=7\r\ntxt:abc\r\n";
+
+    #[test]
+    fn test_parser_matches_decode() -> Result<()> {
+        let expected = RespFrame::decode(&mut BytesMut::from(NESTED))?;
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(NESTED);
+        assert_eq!(parser.decode(&mut buf)?, Some(expected));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_resumes_byte_by_byte() -> Result<()> {
+        let expected = RespFrame::decode(&mut BytesMut::from(NESTED))?;
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::new();
+        for (i, b) in NESTED.iter().enumerate() {
+            buf.extend_from_slice(&[*b]);
+            let ret = parser.decode(&mut buf)?;
+            if i + 1 < NESTED.len() {
+                assert_eq!(ret, None);
+            } else {
+                assert_eq!(ret, Some(expected.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_consumes_completed_elements() -> Result<()> {
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"*3\r\n$3\r\nget\r\n$5\r\nhel"[..]);
+        assert_eq!(parser.decode(&mut buf)?, None);
+        // 已完成的元素和 bulk 的长度行都已取走，只剩下未到齐的内容
+        assert_eq!(&buf[..], b"hel");
+        buf.extend_from_slice(b"lo\r\n:+7\r\n+OK\r\n");
+        let expected = RespArray::new([b"get".into(), b"hello".into(), 7.into()]);
+        assert_eq!(parser.decode(&mut buf)?, Some(expected.into()));
+        assert_eq!(parser.decode(&mut buf)?, Some(RespFrame::from("OK")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_errors() {
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"GET a\r\n"[..]);
+        assert_eq!(
+            parser.decode(&mut buf),
+            Err(RespError::UnknownFrameType(b'G'))
+        );
+        assert_eq!(&buf[..], b"GET a\r\n");
+
+        let mut buf = BytesMut::from(&b"*1\r\n?x\r\n"[..]);
+        assert!(matches!(
+            parser.decode(&mut buf),
+            Err(RespError::InvalidFrameType(_))
+        ));
+
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"$3\r\nabcd\r\n"[..]);
+        assert!(matches!(
+            parser.decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_parser_limits() {
+        let limits = ProtocolLimits {
+            max_bulk_len: 4,
+            max_multibulk_len: 2,
+            max_depth: 2,
+            max_inline_len: 8,
+        };
+        let cases: [(&[u8], &str); 5] = [
+            (b"$5\r\n", "invalid bulk length"),
+            (b"*3\r\n", "invalid multibulk length"),
+            (b"%3\r\n", "invalid multibulk length"),
+            (b"*1\r\n*1\r\n*1\r\n", "too deeply nested request"),
+            (b"*1\r\n+012345678", "too big inline request"),
+        ];
+        for (input, msg) in cases {
+            let mut parser = RespParser::with_limits(limits);
+            let ret = parser.decode(&mut BytesMut::from(input));
+            assert_eq!(ret, Err(RespError::LimitExceeded(msg.to_string())));
+        }
+
+        // 恰好在限制内的请求正常解析
+        let mut parser = RespParser::with_limits(limits);
+        let mut buf = BytesMut::from(&b"*2\r\n*1\r\n$4\r\nabcd\r\n+ok\r\n"[..]);
+        assert!(matches!(parser.decode(&mut buf), Ok(Some(_))));
+    }
+
+    #[test]
+    fn test_limits_from_args() -> Result<()> {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(ProtocolLimits::from_args(args(""))?, ProtocolLimits::default());
+
+        let limits = ProtocolLimits::from_args(args(
+            "--proto-max-bulk-len 1mb --proto-max-multibulk-len 100 \
+             --proto-max-nesting-depth 4 --proto-inline-max-size 2K",
+        ))?;
+        assert_eq!(
+            limits,
+            ProtocolLimits {
+                max_bulk_len: 1024 * 1024,
+                max_multibulk_len: 100,
+                max_depth: 4,
+                max_inline_len: 2000,
+            }
+        );
+
+        for bad in ["--proto-max-bulk-len", "--proto-max-bulk-len 0", "--proto-max-bulk-len 1xb", "--foo 1", "bar"] {
+            assert!(ProtocolLimits::from_args(args(bad)).is_err(), "{}", bad);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::ops::Deref;
+use bytes::{Buf, BytesMut};
+use super::{RespDecode, RespEncode, RespError, RespFrame, calc_total_length, parse_length, CRLF_LEN, put_header, header_len};
+
+/// 服务端主动推送的消息（例如 pub/sub），格式与数组相同
+#[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
+}
+
+// - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+impl RespEncode for RespPush {
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'>', self.len());
+        for frame in self.0 {
+            frame.encode_to(buf);
+        }
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.iter().map(RespEncode::encoded_len).sum::<usize>()
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let total_length = calc_total_length(buf, end, len, Self::PREFIX)?;
+        if buf.len() < total_length {
+            return Err(RespError::NotComplete);
+        }
+        buf.advance(end + CRLF_LEN);
+        let mut frames = Vec::with_capacity(len);
+        for _ in 0..len {
+            frames.push(RespFrame::decode(buf)?);
+        }
+        Ok(RespPush::new(frames))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX)
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::BulkString;
+
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b">3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$5\r\nhello\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let expected = RespPush::new([b"message".into(), b"ch".into(), b"hello".into()]);
+        assert_eq!(frame, expected.into());
+
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n");
+        assert_eq!(RespPush::decode(&mut buf).unwrap_err(), RespError::NotComplete);
+        Ok(())
+    }
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = RespPush::new([BulkString::from("message").into(), 1.into()]).into();
+        assert_eq!(frame.encode(), b">2\r\n$7\r\nmessage\r\n:+1\r\n");
+    }
+}
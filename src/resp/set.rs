@@ -1,9 +1,9 @@
 use std::ops::Deref;
 use bytes::{Buf, BytesMut};
 
-use super::{RespDecode, RespEncode, RespError, RespFrame, calc_total_length, parse_length, CRLF_LEN, BUF_CAP};
+use super::{RespDecode, RespEncode, RespError, RespFrame, calc_total_length, parse_length, CRLF_LEN, put_header, header_len};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
 pub struct RespSet(pub(crate) Vec<RespFrame>);
 
 impl RespSet {
@@ -14,13 +14,14 @@ impl RespSet {
 
 // "~<number-of-elements>\r\n<element-1>...<element-n>"
 impl RespEncode for RespSet {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        buf.extend_from_slice(&format!("~{}\r\n", self.len()).into_bytes());
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'~', self.len());
         for frame in self.0 {
-            buf.extend_from_slice(&frame.encode())
+            frame.encode_to(buf);
         }
-        buf
+    }
+    fn encoded_len(&self) -> usize {
+        header_len(self.len()) + self.iter().map(RespEncode::encoded_len).sum::<usize>()
     }
 }
 
@@ -64,7 +65,7 @@ mod tests {
         let  frame: RespSet = RespSet::new([
             SimpleString::new("value".to_string()).into(),
             BulkString::new("world".to_string()).into()
-        ]).into();
+        ]);
         assert_eq!(frame.encode(),b"~2\r\n+value\r\n$5\r\nworld\r\n");
     }
     #[test]
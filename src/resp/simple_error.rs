@@ -2,7 +2,7 @@ use std::ops::Deref;
 use bytes::BytesMut;
 use super::{RespDecode, RespEncode, RespError, extract_simple_frame_data, CRLF_LEN};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct SimpleError(pub(crate) String);
 
 impl Deref for SimpleError {
@@ -19,8 +19,17 @@ impl SimpleError {
 }
 //error: "-Error message\r\n"
 impl RespEncode for SimpleError {
-    fn encode(self) -> Vec<u8> {
-        format!("-{}\r\n", self.0).into_bytes()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"-");
+        // 错误信息可能带有客户端输入，换行会截断帧，统一替换成空格
+        buf.extend(self.0.bytes().map(|b| match b {
+            b'\r' | b'\n' => b' ',
+            b => b,
+        }));
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        1 + self.0.len() + CRLF_LEN
     }
 }
 
@@ -86,5 +95,13 @@ mod tests {
         assert_eq!(frame.encode(),b"-Error message\r\n");
 
     }
+    #[test]
+    fn test_simple_error_encode_strips_crlf(){
+        let frame :RespFrame = SimpleError::new("ERR bad\r\n+OK").into();
+        let len = frame.encoded_len();
+        let buf = frame.encode();
+        assert_eq!(buf,b"-ERR bad  +OK\r\n");
+        assert_eq!(buf.len(),len);
+    }
 
 }
\ No newline at end of file
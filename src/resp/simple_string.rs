@@ -3,7 +3,7 @@ use bytes::BytesMut;
 use crate::{RespDecode, RespEncode, RespError};
 use crate::resp::{extract_simple_frame_data, CRLF_LEN};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct SimpleString(pub(crate) String);
 
 impl Deref for SimpleString {
@@ -20,8 +20,13 @@ impl SimpleString {
 }
 //- simple string:"+OK\r\n"
 impl RespEncode for SimpleString {
-    fn encode(self) -> Vec<u8> {
-        format!("+{}\r\n", self.0).into_bytes()
+    fn encode_to(self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"+");
+        buf.extend_from_slice(self.0.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        1 + self.0.len() + CRLF_LEN
     }
 }
 impl RespDecode for SimpleString {
@@ -84,7 +89,7 @@ mod tests {
     fn test_decode_simple_string() -> Result<()> {
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b"+OK\r\n");
-        let frame: SimpleString = SimpleString::decode(&mut buf)?.into();
+        let frame: SimpleString = SimpleString::decode(&mut buf)?;
         assert_eq!(frame, SimpleString::new("OK".to_string()));
         buf.extend_from_slice(b"+hello\r");
         //
@@ -92,7 +97,7 @@ mod tests {
         // println!("{:?}", ret.unwrap_err());
         assert_eq!(ret.unwrap_err(), RespError::NotComplete);
         buf.put_u8(b'\n');
-        let frame: SimpleString = SimpleString::decode(&mut buf)?.into();
+        let frame: SimpleString = SimpleString::decode(&mut buf)?;
         assert_eq!(frame, SimpleString::new("hello".to_string()));
         Ok(())
     }
@@ -0,0 +1,88 @@
+use bytes::{Buf, BytesMut};
+use super::{RespDecode, RespEncode, RespError, parse_length, put_header, header_len, CRLF_LEN};
+
+// 格式名固定为 3 个字节，例如 txt、mkd
+const FORMAT_LEN: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+pub struct VerbatimString {
+    pub(crate) format: [u8; FORMAT_LEN],
+    pub(crate) data: Vec<u8>,
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; FORMAT_LEN], data: impl Into<Vec<u8>>) -> Self {
+        VerbatimString { format, data: data.into() }
+    }
+    pub fn format(&self) -> &[u8] {
+        &self.format
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+// - verbatim string: "=<length>\r\n<format>:<data>\r\n"，长度包含 "<format>:"
+impl RespEncode for VerbatimString {
+    fn encode_to(self, buf: &mut BytesMut) {
+        put_header(buf, b'=', FORMAT_LEN + 1 + self.data.len());
+        buf.extend_from_slice(&self.format);
+        buf.extend_from_slice(b":");
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+    }
+    fn encoded_len(&self) -> usize {
+        let len = FORMAT_LEN + 1 + self.data.len();
+        header_len(len) + len + CRLF_LEN
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static str = "=";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let remained = &buf[end + CRLF_LEN..];
+        if remained.len() < len + CRLF_LEN {
+            return Err(RespError::NotComplete);
+        }
+        if len <= FORMAT_LEN || remained[FORMAT_LEN] != b':' {
+            return Err(RespError::InvalidFrame(format!(
+                "expect: VerbatimString(<format>:<data>), got: {:?}",
+                &remained[..len]
+            )));
+        }
+        buf.advance(end + CRLF_LEN);
+        let data = buf.split_to(len + CRLF_LEN);
+        let mut format = [0; FORMAT_LEN];
+        format.copy_from_slice(&data[..FORMAT_LEN]);
+        Ok(VerbatimString::new(format, data[FORMAT_LEN + 1..len].to_vec()))
+    }
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN + len + CRLF_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_verbatim_string_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"=15\r\ntxt:Some string\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::new(*b"txt", "Some string").into());
+
+        buf.extend_from_slice(b"=3\r\ntxt\r\n");
+        assert!(matches!(VerbatimString::decode(&mut buf), Err(RespError::InvalidFrame(_))));
+        Ok(())
+    }
+    #[test]
+    fn test_verbatim_string_encode() {
+        let frame: RespFrame = VerbatimString::new(*b"mkd", "# title").into();
+        assert_eq!(frame.encode(), b"=11\r\nmkd:# title\r\n");
+    }
+}